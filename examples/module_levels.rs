@@ -0,0 +1,37 @@
+//! Time `Log::enabled` with 50 `Config::module_level` rules, natively:
+//!
+//! ```sh
+//! cargo run --release --example module_levels
+//! ```
+use log::{Level, LevelFilter, Metadata};
+use std::time::Instant;
+
+const RULES: usize = 50;
+const CHECKS: usize = 100_000;
+
+fn main() {
+    let config = (0..RULES).fold(wasm_logger::Config::new(Level::Info), |config, i| {
+        config.module_level(&format!("app::module_{i}"), LevelFilter::Debug)
+    });
+    wasm_logger::init(config);
+    // The targets of the rules, their children, and targets which match no rule
+    let targets: Vec<String> = (0..RULES)
+        .flat_map(|i| [format!("app::module_{i}"), format!("app::module_{i}::view")])
+        .chain((0..5).map(|i| format!("vendor::crate_{i}")))
+        .collect();
+    let start = Instant::now();
+    let enabled = (0..CHECKS)
+        .filter(|i| {
+            let metadata = Metadata::builder()
+                .level(Level::Debug)
+                .target(&targets[i % targets.len()])
+                .build();
+            log::logger().enabled(&metadata)
+        })
+        .count();
+    println!(
+        "{CHECKS} checks over {} targets in {:?}, {enabled} enabled",
+        targets.len(),
+        start.elapsed()
+    );
+}
@@ -0,0 +1,169 @@
+//! The rendering of the entries: `RenderedLog`, the text and styles of a console call,
+//! and the `Formatter` trait of `Config::formatter` to customize it.
+use crate::{Config, MessageLocation, Style};
+use log::{Level, Record};
+use wasm_bindgen::JsValue;
+
+/// A log entry as the arguments of a console call: a text with `%c` directives,
+/// and the styles which they apply, in order. See `render`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedLog {
+    /// The text of the entry, with a `%c` directive before each styled segment
+    pub text: String,
+    /// The CSS of each `%c` directive of `text`, in order
+    pub styles: Vec<String>,
+}
+
+impl RenderedLog {
+    /// Join `segments` of `(style, text)` after the unstyled `lead` text. If there are more
+    /// segments than `max_styles`, the last allowed directive resets the style and the
+    /// text of all remaining segments follows it unstyled.
+    pub(crate) fn from_segments(
+        lead: String,
+        segments: Vec<(&str, String)>,
+        max_styles: usize,
+    ) -> Self {
+        let mut rendered = RenderedLog {
+            text: lead,
+            styles: Vec::new(),
+        };
+        let collapse_at = if segments.len() > max_styles {
+            max_styles.checked_sub(1)
+        } else {
+            None
+        };
+        for (i, (style, text)) in segments.into_iter().enumerate() {
+            match collapse_at {
+                Some(at) if i > at => {}
+                Some(at) if i == at => {
+                    rendered.text.push_str("%c");
+                    rendered.styles.push(String::new());
+                }
+                None if max_styles == 0 => {}
+                _ => {
+                    rendered.text.push_str("%c");
+                    rendered.styles.push(style.to_string());
+                }
+            }
+            rendered.text.push_str(&text);
+        }
+        rendered
+    }
+
+    /// The text without its `%c` directives, for the outputs which are not styled
+    pub fn plain_text(&self) -> String {
+        self.text.replace("%c", "")
+    }
+
+    /// The entry as HTML, with a `<span style="...">` for each styled segment, e.g. to show
+    /// it in a panel of the page as it looks in the devtools. The text and the styles are
+    /// HTML-escaped. The line breaks of the text are kept as is, so the container needs
+    /// `white-space: pre-wrap` to show them.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// let rendered = wasm_logger::RenderedLog {
+    ///     text: "%cWARN%c <img src=x> & more".to_string(),
+    ///     styles: vec!["color: orange".to_string(), String::new()],
+    /// };
+    /// let expected = concat!(
+    ///     "<span style=\"color: orange\">WARN</span>",
+    ///     "<span style=\"\"> &lt;img src=x&gt; &amp; more</span>",
+    /// );
+    /// assert_eq!(rendered.to_html(), expected);
+    /// ```
+    pub fn to_html(&self) -> String {
+        let mut segments = self.text.split("%c");
+        let mut html = String::with_capacity(self.text.len() * 2);
+        push_html_escaped(&mut html, segments.next().unwrap_or_default());
+        for (i, segment) in segments.enumerate() {
+            html.push_str("<span style=\"");
+            push_html_escaped(&mut html, self.styles.get(i).map_or("", String::as_str));
+            html.push_str("\">");
+            push_html_escaped(&mut html, segment);
+            html.push_str("</span>");
+        }
+        html
+    }
+
+    /// The arguments of the console call: the text, then the styles
+    pub(crate) fn args(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.text).chain(&self.styles)
+    }
+
+    pub(crate) fn console_args(&self) -> js_sys::Array {
+        self.args().map(|s| JsValue::from_str(s)).collect()
+    }
+}
+
+/// Renders the entries, see `Config::formatter`
+pub trait Formatter: Send + Sync {
+    /// Render `record` as the arguments of a console call. It is called for each entry
+    /// which passes the filters, before `Config::pre_emit`; `ctx` gives the built-in
+    /// rendering and styles to build on.
+    fn format(&self, record: &Record<'_>, ctx: &FormatContext<'_>) -> RenderedLog;
+}
+
+/// What a `Formatter` can reuse from the logger
+pub struct FormatContext<'a> {
+    pub(crate) config: &'a Config,
+    pub(crate) style: &'a Style,
+}
+
+impl FormatContext<'_> {
+    /// Render `record` in the built-in layout configured by the config
+    pub fn render_default(&self, record: &Record<'_>) -> RenderedLog {
+        self.config.render_builtin(self.style, record)
+    }
+
+    /// The CSS of the level badge of `level`, for the current color scheme
+    pub fn level_style(&self, level: Level) -> &str {
+        self.style.level(level)
+    }
+}
+
+/// The hook of `Config::dynamic`
+pub type DynamicHook = Box<dyn Fn(&Record<'_>, &mut EffectiveConfig) + Send + Sync>;
+
+/// The settings of the layout which the hook of `Config::dynamic` can change for one
+/// entry. They start from the config, and the layout toggles only apply to the styled
+/// layouts, not to `Config::json_format`, `Config::json_lite` and `Config::logfmt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EffectiveConfig {
+    /// Where the message is, see `Config::message_on_new_line`
+    pub message_location: MessageLocation,
+    /// Whether the timestamp of `Config::timestamp_format` is shown, if there is one
+    pub show_timestamp: bool,
+    /// Whether the file and line of the record are shown
+    pub show_location: bool,
+    /// Whether the entry is styled. If `false`, it is logged as plain text.
+    pub styled: bool,
+    /// The CSS of the message, replacing the one of the config and of a `#` in the target.
+    /// `None` keeps them.
+    pub message_css: Option<String>,
+}
+
+/// The built-in layouts as a `Formatter`, e.g. to wrap them in another formatter
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultFormatter;
+
+impl Formatter for DefaultFormatter {
+    fn format(&self, record: &Record<'_>, ctx: &FormatContext<'_>) -> RenderedLog {
+        ctx.render_default(record)
+    }
+}
+
+/// Append `s` to `out` with the characters which are special in HTML text and attributes
+/// escaped
+fn push_html_escaped(out: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
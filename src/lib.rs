@@ -1,12 +1,316 @@
 //! A simple logger for front end wasm web app.
 //!
 //! Please see [README](https://gitlab.com/limira-rs/wasm-logger/blob/master/README.md) for documentation.
+//!
+//! The traits to extend the logger are in the `format` and `sink` modules; their items
+//! are also re-exported at the root of the crate.
+//!
+//! ## Helpers
+//!
+//! Besides the `log` macros, functions such as `log_duration`, `log_bytes` or `log_err`
+//! log an entry on behalf of their caller. Unless their documentation says otherwise, the
+//! entry has the file and line of the caller, like the entries of the macros, and the
+//! target `wasm_logger`, and is subject to the level and target filters.
+//!
+//! ## Nested entries
+//!
+//! An entry logged while the logger handles another one, e.g. from a `Display`
+//! implementation, a `Formatter`, a hook of the config or a `Sink`, is dropped, since it
+//! would be interleaved with the entry being handled. The first entry dropped this way is
+//! reported with `Config::internal_log_method`, the next ones are dropped silently.
 #![deny(missing_docs)]
-use log::{Level, Log, Metadata, Record};
+pub use format::{
+    DefaultFormatter, DynamicHook, EffectiveConfig, FormatContext, Formatter, RenderedLog,
+};
+pub use log::Level;
+use log::{LevelFilter, Log, Metadata, Record};
+pub use sink::{DeliveryFailureHook, EmitDecision, PreEmitHook, RecordData, RecordHook, Sink};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use wasm_bindgen::prelude::*;
 use web_sys::console;
 
+pub mod format;
+pub mod sink;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &JsValue, timeout: u32) -> JsValue;
+
+    #[wasm_bindgen(js_name = setInterval)]
+    fn set_interval(handler: &JsValue, timeout: u32) -> JsValue;
+
+    #[wasm_bindgen(js_name = clearInterval)]
+    fn clear_interval(id: &JsValue);
+}
+
+/// The version of the fields of `Config::json_format` and `Config::json_lite`, which is
+/// increased when they change, the default of `Config::schema_version`
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The frame counter of `next_frame`
+static FRAME: AtomicU64 = AtomicU64::new(0);
+
+/// Set by `Config::assume_worker`, so that the features which need a window are disabled
+static ASSUME_WORKER: AtomicBool = AtomicBool::new(false);
+
+/// Set once `init` successfully installed the logger
+static INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Set by `shutdown`, turns every subsequent log into a no-op
+static SHUT_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// The levels which `set_level_enabled` did not turn off, one bit per level, see `level_bit`
+static ENABLED_LEVELS: AtomicU8 = AtomicU8::new(0b1_1111);
+
+/// The target prefixes silenced by `mute_target`
+static MUTED_TARGETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// The target prefixes of `focus`, empty when focus is not active
+static FOCUSED_TARGETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// The gauges of `set_gauge`, by name
+static GAUGES: RwLock<BTreeMap<String, f64>> = RwLock::new(BTreeMap::new());
+
+/// The logger installed by `init`, to reach its state from the functions of this crate
+static LOGGER: OnceLock<Arc<WasmLogger>> = OnceLock::new();
+
+/// The configs of `register_config`, each with its own logger
+static CONFIGS: RwLock<Vec<(String, Arc<WasmLogger>)>> = RwLock::new(Vec::new());
+
+/// The logger of the config picked by `use_config`, or `None` for the one of `init`
+static ACTIVE: RwLock<Option<Arc<WasmLogger>>> = RwLock::new(None);
+
+/// Set once `pre_init_buffer` installed `BUFFERING_LOGGER` as the global logger
+static PRE_INIT_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// The last entries kept by `Config::keep_tail`, oldest first
+static TAIL: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// The records held by `pre_init_buffer` until `init`, and the number of records dropped
+/// because it was full
+static PRE_INIT_BUFFER: Mutex<(Vec<BufferedRecord>, u64)> = Mutex::new((Vec::new(), 0));
+
+/// An event listener installed by the logger, removed by `shutdown`
+struct Listener {
+    target: web_sys::EventTarget,
+    event: &'static str,
+    callback: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+thread_local! {
+    static LISTENERS: RefCell<Vec<Listener>> = const { RefCell::new(Vec::new()) };
+
+    /// The channel of `Config::broadcast_channel`, and the id of this tab
+    static BROADCAST: RefCell<Option<(web_sys::BroadcastChannel, String)>> = const { RefCell::new(None) };
+
+    /// The lines collected by the running `capture`s, outermost first
+    static CAPTURES: RefCell<Vec<Vec<String>>> = const { RefCell::new(Vec::new()) };
+
+    /// The interval of `Config::flush_interval_ms`
+    static FLUSH_INTERVAL: RefCell<Option<FlushInterval>> = const { RefCell::new(None) };
+
+    /// The entries collected for `Config::remote_url` since the last flush
+    static REMOTE_BUFFER: RefCell<VecDeque<String>> = const { RefCell::new(VecDeque::new()) };
+
+    /// Set while `WasmLogger::log` handles an entry, to detect the entries logged meanwhile,
+    /// e.g. by a `Display` implementation or a `Formatter`
+    static IN_LOG: Cell<bool> = const { Cell::new(false) };
+
+    /// Set once the nested entries have been reported
+    static NESTED_REPORTED: Cell<bool> = const { Cell::new(false) };
+
+    /// The group of `Config::auto_group_by_target` which is open: its target, the
+    /// `Date::now()` of its last entry, and its id
+    static AUTO_GROUP: RefCell<Option<(String, f64, u32)>> = const { RefCell::new(None) };
+
+    /// The id of the last group opened by `Config::auto_group_by_target`
+    static AUTO_GROUP_ID: Cell<u32> = const { Cell::new(0) };
+
+    /// The socket of `Config::websocket_sink` and its queue
+    static WEBSOCKET: RefCell<Option<WebSocketSink>> = const { RefCell::new(None) };
+
+    /// The targets already reported by `Config::warn_if_slow`
+    static SLOW_TARGETS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+
+    /// The ids and names of the `with_context` and `span` scopes which are running,
+    /// outermost first
+    static CONTEXT: RefCell<Vec<(u64, String)>> = const { RefCell::new(Vec::new()) };
+
+    /// The id of the last scope pushed on `CONTEXT`
+    static CONTEXT_ID: Cell<u64> = const { Cell::new(0) };
+
+    /// The ids of the scopes whose console group is open, see `Config::group_from_context`
+    static CONTEXT_GROUPS: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+
+    /// The level, target and message of the last entry of `Config::dedup_consecutive`, and
+    /// the number of its repeats since it was logged
+    static LAST_ENTRY: RefCell<Option<(Level, String, String, u64)>> = const { RefCell::new(None) };
+
+    /// The depth of `indent_push`, see `Config::indent_width`
+    static INDENT_DEPTH: Cell<usize> = const { Cell::new(0) };
+
+    /// The console method chosen by `log_as` for the entry which it logs
+    static METHOD_OVERRIDE: Cell<Option<ConsoleMethod>> = const { Cell::new(None) };
+
+    /// The layout chosen by `Config::dynamic` for the entry being rendered
+    static LAYOUT_OVERRIDE: Cell<Option<LayoutOverride>> = const { Cell::new(None) };
+
+    /// The timestamps of `Config::elide_repeated_timestamps` for the entry being rendered by
+    /// the logger, `None` when rendering for `render`
+    static TIMESTAMP_ELISION: RefCell<Option<TimestampElision>> = const { RefCell::new(None) };
+
+    /// The console entries held by `Config::defer_until_ready`, until `mark_ready`
+    static DEFERRED: RefCell<Option<Vec<DeferredEntry>>> = const { RefCell::new(None) };
+}
+
+/// The layout toggles of an `EffectiveConfig`, see `Config::dynamic`
+#[derive(Clone, Copy)]
+struct LayoutOverride {
+    message_location: MessageLocation,
+    timestamp: bool,
+    location: bool,
+}
+
+/// See `TIMESTAMP_ELISION`
+struct TimestampElision {
+    /// The timestamp of the last entry emitted by the logger, elided if it repeats
+    last: Option<String>,
+    /// The timestamp of the entry being rendered, kept by the logger once it is emitted
+    rendered: Option<String>,
+}
+
+/// The `setInterval` of `Config::flush_interval_ms`, with the handler it calls
+struct FlushInterval {
+    id: JsValue,
+    _handler: Closure<dyn FnMut()>,
+}
+
+/// A console entry held by `Config::defer_until_ready`, rendered when it was logged
+struct DeferredEntry {
+    level: Level,
+    /// The method chosen by `log_as`, if any
+    method: Option<ConsoleMethod>,
+    /// The target of its `Config::auto_group_by_target` group, if any
+    group: Option<String>,
+    /// The children of its `Config::rich_grouping` group
+    metadata: Vec<String>,
+    rendered: RenderedLog,
+}
+
+/// Clears `IN_LOG` when dropped, even if the handling of the entry panics
+struct InLogGuard;
+
+impl Drop for InLogGuard {
+    fn drop(&mut self) {
+        IN_LOG.with(|in_log| in_log.set(false));
+    }
+}
+
+/// Leaves the scope of `with_context` or `span` with this id when dropped, even if it
+/// panics or an enclosing scope has been left first
+struct ContextGuard(u64);
+
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|context| context.borrow_mut().retain(|(id, _)| *id != self.0));
+        sync_context_groups();
+    }
+}
+
+/// Enter the scope `name`, until the returned guard is dropped
+fn enter_context(name: &str) -> ContextGuard {
+    let id = CONTEXT_ID.with(|id| {
+        id.set(id.get() + 1);
+        id.get()
+    });
+    CONTEXT.with(|context| context.borrow_mut().push((id, name.to_string())));
+    sync_context_groups();
+    ContextGuard(id)
+}
+
+/// The names of the running scopes, joined by ` › `, or `None` if there are none
+fn context_path() -> Option<String> {
+    CONTEXT.with(|context| {
+        let context = context.borrow();
+        (!context.is_empty()).then(|| {
+            context
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(" › ")
+        })
+    })
+}
+
+/// Make the open console groups of `Config::group_from_context` match the running scopes:
+/// close the groups of the scopes which have been left, innermost first, along with the
+/// groups opened inside them, then open the groups of the new scopes. A scope left out of
+/// order thus closes the groups nested in it, which are opened again without it.
+fn sync_context_groups() {
+    if !active_logger().is_some_and(|logger| logger.config.group_from_context)
+        || SHUT_DOWN.load(Ordering::Relaxed)
+    {
+        return;
+    }
+    CONTEXT.with(|context| {
+        CONTEXT_GROUPS.with(|groups| {
+            let context = context.borrow();
+            let mut groups = groups.borrow_mut();
+            let kept = groups
+                .iter()
+                .zip(context.iter())
+                .take_while(|(group, (id, _))| *group == id)
+                .count();
+            if kept == groups.len() && kept == context.len() {
+                return;
+            }
+            // The group of `Config::auto_group_by_target` is the innermost one
+            close_auto_group();
+            for _ in kept..groups.len() {
+                console::group_end();
+            }
+            groups.truncate(kept);
+            for (id, name) in &context[kept..] {
+                console::group_1(&JsValue::from_str(name));
+                groups.push(*id);
+            }
+        })
+    });
+}
+
+/// Close the console groups of `Config::group_from_context`, see `shutdown`
+fn close_context_groups() {
+    for _ in CONTEXT_GROUPS.with(|groups| groups.take()) {
+        console::group_end();
+    }
+}
+
+fn add_listener(
+    target: web_sys::EventTarget,
+    event: &'static str,
+    callback: Closure<dyn FnMut(web_sys::Event)>,
+) {
+    if target
+        .add_event_listener_with_callback(event, callback.as_ref().unchecked_ref())
+        .is_ok()
+    {
+        LISTENERS.with(|listeners| {
+            listeners.borrow_mut().push(Listener {
+                target,
+                event,
+                callback,
+            })
+        });
+    }
+}
+
 /// Specify timestamp format
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TimestampFormat {
     /// https://www.rfc-editor.org/rfc/rfc2822
     Rfc2822,
@@ -16,17 +320,324 @@ pub enum TimestampFormat {
 
     /// Custom format string for chrono::DateTime
     Custom(String),
+
+    /// The time of day in UTC, with milliseconds, e.g. `14:03:22.123`, for the entries of
+    /// a single session
+    TimeOnly,
+
+    /// Milliseconds since the page started, from `performance.now()`, with the number of
+    /// fractional digits of `Config::performance_precision`
+    Performance,
+}
+
+/// Specify how the level of a log entry is displayed
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LevelDisplay {
+    /// The full level name: `TRACE`, `DEBUG`, `INFO`, `WARN`, `ERROR`
+    Full,
+    /// Only the first character of the level name: `T`, `D`, `I`, `W`, `E`.
+    /// The badge keeps the level color.
+    Initial,
+}
+
+/// Specify the case of the level label, see `Config::level_case`
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Case {
+    /// `INFO`, or `I` with `LevelDisplay::Initial`
+    Upper,
+    /// `info`, or `i` with `LevelDisplay::Initial`
+    Lower,
+    /// `Info`, or `I` with `LevelDisplay::Initial`
+    Title,
+}
+
+/// Specify what fills the location slot when the file of a record is unknown
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LocationFallback {
+    /// Show the target of the record (default)
+    Target,
+    /// Show the module path of the record, or its target if the module path is unknown too
+    ModulePath,
+    /// Show no location at all
+    Hidden,
+    /// Show this text, e.g. `<js>`, see `Config::synthetic_location`
+    Synthetic(String),
+}
+
+/// Specify which part of a message longer than `Config::max_message_len` is kept
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TruncateMode {
+    /// Keep the start of the message (default)
+    Head,
+    /// Keep the end of the message, e.g. for an error code which comes last
+    Tail,
+    /// Keep both ends of the message, and elide its middle
+    Middle,
+}
+
+/// Specify what is done with a record whose message is empty, e.g. a `trace!()` which only
+/// has key-values
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmptyMessagePolicy {
+    /// Log the entry with nothing after its header (default)
+    Show,
+    /// Do not log the record at all. It is counted as filtered by `suppression_stats`.
+    Skip,
+    /// Show this text, e.g. `<no message>`, in place of the message
+    Placeholder(String),
+}
+
+/// Specify how levels are colored
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StyleMode {
+    /// White text on a background of the level color (default)
+    Badge,
+    /// Text in the level color, without background
+    Text,
+}
+
+/// A preset of `Config::profile`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// Everything, with the details which help debugging
+    Development,
+    /// The warnings and errors only, in a compact machine-readable form
+    Production,
+    /// Everything, as plain deterministic text kept for assertions
+    Testing,
+}
+
+/// A method of the browser console
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsoleMethod {
+    /// `console.debug`
+    Debug,
+    /// `console.log`
+    Log,
+    /// `console.info`
+    Info,
+    /// `console.warn`
+    Warn,
+    /// `console.error`
+    Error,
+    /// `console.trace`, which also prints the stack trace
+    Trace,
+}
+
+impl ConsoleMethod {
+    fn call(self, args: &js_sys::Array) {
+        match self {
+            ConsoleMethod::Debug => console::debug(args),
+            ConsoleMethod::Log => console::log(args),
+            ConsoleMethod::Info => console::info(args),
+            ConsoleMethod::Warn => console::warn(args),
+            ConsoleMethod::Error => console::error(args),
+            ConsoleMethod::Trace => console::trace(args),
+        }
+    }
+}
+
+/// The visual importance of a level, which picks its color and its console method,
+/// see `Config::severity_override`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// The color of `Level::Trace`, with `console.debug`
+    Low,
+    /// The color of `Level::Info`, with `console.info`
+    Normal,
+    /// The color of `Level::Warn`, with `console.warn`
+    High,
+    /// The color of `Level::Error`, with `console.error`
+    Critical,
+}
+
+impl Severity {
+    /// The level whose color is used for this severity
+    fn color_level(self) -> Level {
+        match self {
+            Severity::Low => Level::Trace,
+            Severity::Normal => Level::Info,
+            Severity::High => Level::Warn,
+            Severity::Critical => Level::Error,
+        }
+    }
+
+    fn console_method(self) -> ConsoleMethod {
+        match self {
+            Severity::Low => ConsoleMethod::Debug,
+            Severity::Normal => ConsoleMethod::Info,
+            Severity::High => ConsoleMethod::Warn,
+            Severity::Critical => ConsoleMethod::Error,
+        }
+    }
+}
+
+/// Log a diagnostic of `wasm-logger` itself with `method`, if any
+fn internal_log(method: Option<ConsoleMethod>, message: &str) {
+    if let Some(method) = method {
+        method.call(&js_sys::Array::of1(&JsValue::from_str(message)));
+    }
+}
+
+/// Specify the layout of a log entry
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum OutputFormat {
+    /// The human-readable layout, styled with `%c` directives
+    Styled,
+    /// The human-readable layout of `env_logger`
+    EnvLogger,
+    /// A single-line JSON object
+    #[cfg(feature = "json")]
+    Json,
+    /// A single-line JSON object of the common fields, without `serde_json`
+    JsonLite,
+    /// Space-separated `key=value` pairs
+    Logfmt,
 }
 
 /// Specify what to be logged
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct Config {
     level: Level,
+    level_schedule: Vec<(u8, u8, Level)>,
     module_prefix: Option<String>,
+    exact_targets: Vec<String>,
+    module_levels: Vec<(String, LevelFilter)>,
+    always_on_targets: Vec<String>,
     timestamp_format: Option<TimestampFormat>,
+    /// The items of a `TimestampFormat::Custom`, parsed once
+    #[cfg_attr(feature = "serde", serde(skip))]
+    timestamp_items: Option<Vec<chrono::format::Item<'static>>>,
+    timestamp_levels: Option<Vec<Level>>,
+    elide_repeated_timestamps: bool,
+    performance_precision: u8,
     message_location: MessageLocation,
+    level_display: LevelDisplay,
+    level_case: Case,
+    level_emoji_prefix: bool,
+    level_emojis: [String; 5],
+    indent_continuation: bool,
+    newline_indent: String,
+    message_prefix: String,
+    indent_width: usize,
+    message_suffix: String,
+    global_fields: Vec<(String, String)>,
+    show_global_fields: bool,
+    kv_pair_separator: String,
+    kv_separator: String,
+    max_token_len: Option<usize>,
+    sanitize_control_chars: bool,
+    max_message_len: Option<usize>,
+    truncate_mode: TruncateMode,
+    truncation_indicator: String,
+    output_format: OutputFormat,
+    schema_version: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    formatter: Option<Box<dyn Formatter>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dynamic: Option<DynamicHook>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pre_emit: Option<PreEmitHook>,
+    /// The `Config::add_sink`s, with the bits of their levels
+    #[cfg_attr(feature = "serde", serde(skip))]
+    sinks: Vec<(Box<dyn Sink>, u8)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    on_record: Option<RecordHook>,
+    location_fallback: LocationFallback,
+    empty_target_placeholder: String,
+    empty_message: EmptyMessagePolicy,
+    max_logs_per_second: Option<u32>,
+    max_total_logs: Option<usize>,
+    dedup_consecutive: bool,
+    dedup_normalized: bool,
+    contextual_style: Option<String>,
+    group_from_context: bool,
+    debug_uses_console_debug: bool,
+    quiet_info: bool,
+    trace_with_stack: bool,
+    unix_streams: bool,
+    severity_overrides: Vec<(Level, Severity)>,
+    style_mode: StyleMode,
+    ansi_colors: bool,
+    badge_opacity: f32,
+    severity_gradient: Option<f32>,
+    adapt_to_color_scheme: bool,
+    internal_log_method: Option<ConsoleMethod>,
+    strip_path_prefixes: Vec<String>,
+    max_style_segments: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    broadcast_channel: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    remote_url: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    websocket_sink: Option<(String, Level)>,
+    max_buffered_logs: usize,
+    /// Shared with the `fetch`es of `Config::remote_url` which are still pending
+    #[cfg_attr(feature = "serde", serde(skip))]
+    on_delivery_failure: Option<Arc<DeliveryFailureHook>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dom_events: Option<(String, String)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    breadcrumbs: Option<(String, Level)>,
+    breadcrumb_method: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    plain_capture: Option<Arc<Mutex<Vec<String>>>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    persisted: Option<(String, usize)>,
+    tail_len: usize,
+    echo_captured: bool,
+    use_send_beacon: bool,
+    assume_worker: bool,
+    flush_interval_ms: Option<u32>,
+    thousands_separator: Option<char>,
+    si_units: bool,
+    color_by_target: bool,
+    instance_tag: Option<String>,
+    show_frame: bool,
+    separator_char: char,
+    separator_width: usize,
+    separator_level: Level,
+    performance_markers: bool,
+    slow_log_micros: Option<u32>,
+    target_colors: Vec<(String, String)>,
+    #[cfg(feature = "regex")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    highlights: Vec<(regex::Regex, String)>,
+    #[cfg(feature = "regex")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    target_regexes: Vec<(regex::Regex, LevelFilter)>,
+    stack_trace_on_error: bool,
+    trim_stack_frames: Vec<String>,
+    rich_grouping: bool,
+    auto_group_by_target: bool,
+    defer_until_ready: bool,
+    ready_timeout_ms: u32,
+    log_environment_on_init: bool,
+    log_hardware_info: bool,
+    #[cfg(feature = "kv")]
+    timestamp_kv: Option<String>,
+    #[cfg(feature = "kv")]
+    kv_max_depth: usize,
+    #[cfg(feature = "kv")]
+    kv_value_max_len: Option<usize>,
 }
 
 /// Specify where the message will be logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MessageLocation {
     /// The message will be on the same line as other info (level, path...)
     SameLine,
@@ -38,177 +649,7215 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             level: Level::Debug,
+            level_schedule: Vec::new(),
             module_prefix: None,
+            exact_targets: Vec::new(),
+            module_levels: Vec::new(),
+            always_on_targets: Vec::new(),
             message_location: MessageLocation::SameLine,
             timestamp_format: None,
+            timestamp_items: None,
+            timestamp_levels: None,
+            elide_repeated_timestamps: false,
+            performance_precision: 3,
+            level_display: LevelDisplay::Full,
+            level_case: Case::Upper,
+            level_emoji_prefix: false,
+            level_emojis: ["❌", "⚠️", "ℹ️", "🐛", "🔍"].map(String::from),
+            indent_continuation: false,
+            newline_indent: String::new(),
+            message_prefix: String::new(),
+            indent_width: 2,
+            message_suffix: String::new(),
+            global_fields: Vec::new(),
+            show_global_fields: true,
+            kv_pair_separator: " ".to_string(),
+            kv_separator: "=".to_string(),
+            max_token_len: None,
+            sanitize_control_chars: true,
+            max_message_len: None,
+            truncate_mode: TruncateMode::Head,
+            truncation_indicator: "…".to_string(),
+            output_format: OutputFormat::Styled,
+            schema_version: SCHEMA_VERSION,
+            formatter: None,
+            dynamic: None,
+            pre_emit: None,
+            sinks: Vec::new(),
+            on_record: None,
+            location_fallback: LocationFallback::Target,
+            empty_target_placeholder: "<unknown>".to_string(),
+            empty_message: EmptyMessagePolicy::Show,
+            max_logs_per_second: None,
+            max_total_logs: None,
+            dedup_consecutive: false,
+            dedup_normalized: false,
+            contextual_style: None,
+            group_from_context: false,
+            debug_uses_console_debug: false,
+            quiet_info: false,
+            trace_with_stack: false,
+            unix_streams: false,
+            severity_overrides: Vec::new(),
+            style_mode: StyleMode::Badge,
+            ansi_colors: false,
+            badge_opacity: 1.0,
+            severity_gradient: None,
+            adapt_to_color_scheme: false,
+            internal_log_method: Some(ConsoleMethod::Warn),
+            strip_path_prefixes: Vec::new(),
+            max_style_segments: 8,
+            broadcast_channel: None,
+            remote_url: None,
+            websocket_sink: None,
+            max_buffered_logs: 1000,
+            on_delivery_failure: None,
+            dom_events: None,
+            breadcrumbs: None,
+            breadcrumb_method: "addBreadcrumb".to_string(),
+            plain_capture: None,
+            persisted: None,
+            tail_len: 0,
+            echo_captured: false,
+            use_send_beacon: false,
+            assume_worker: false,
+            flush_interval_ms: None,
+            thousands_separator: None,
+            si_units: false,
+            color_by_target: false,
+            instance_tag: None,
+            show_frame: false,
+            separator_char: '─',
+            separator_width: 40,
+            separator_level: Level::Info,
+            performance_markers: false,
+            slow_log_micros: None,
+            rich_grouping: false,
+            auto_group_by_target: false,
+            defer_until_ready: false,
+            ready_timeout_ms: 10_000,
+            log_environment_on_init: false,
+            log_hardware_info: false,
+            target_colors: Vec::new(),
+            #[cfg(feature = "regex")]
+            highlights: Vec::new(),
+            #[cfg(feature = "regex")]
+            target_regexes: Vec::new(),
+            stack_trace_on_error: false,
+            trim_stack_frames: Vec::new(),
+            #[cfg(feature = "kv")]
+            timestamp_kv: None,
+            #[cfg(feature = "kv")]
+            kv_max_depth: 4,
+            #[cfg(feature = "kv")]
+            kv_value_max_len: None,
         }
     }
 }
 
 impl Config {
+    /// Serialize the settings of this config to JSON, e.g. to persist the logging
+    /// preferences of a user in `localStorage`. The outputs, which are opened at `init`,
+    /// and the settings which hold Rust values are omitted: `Config::broadcast_channel`,
+    /// `Config::remote_url`, `Config::websocket_sink`, `Config::dispatch_dom_events`,
+    /// `Config::breadcrumbs`, `Config::capture_plain`, `Config::persist_to_local_storage`,
+    /// `Config::formatter`, `Config::dynamic`, `Config::pre_emit`, `Config::add_sink`,
+    /// `Config::on_record`, `Config::on_delivery_failure` and `Config::highlight`.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use wasm_logger::{describe, Config};
+    ///
+    /// let config = Config::new(log::Level::Trace).module_prefix("app").message_on_new_line();
+    /// let json = config.to_json().unwrap();
+    /// let restored = Config::from_json(&json).unwrap();
+    /// assert_eq!(describe(&restored), describe(&config));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserialize a config serialized by `Config::to_json`. Missing settings take their
+    /// default values.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let mut config: Config = serde_json::from_str(json)?;
+        if let Some(ts_fmt) = config.timestamp_format.take() {
+            config = config.timestamp_format(ts_fmt);
+        }
+        Ok(config)
+    }
+
     /// Specify the maximum level you want to log
     pub fn new(level: Level) -> Self {
         Self {
             level,
-            module_prefix: None,
-            message_location: MessageLocation::SameLine,
-            timestamp_format: None,
+            ..Self::default()
         }
     }
 
+    /// Build a config from the default one with `f`, which receives it by mutable
+    /// reference, e.g. for settings which depend on conditions. `Config::set` applies the
+    /// fluent setters to it, so both forms configure exactly the same settings.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::Level;
+    /// use wasm_logger::Config;
+    ///
+    /// let verbose = true;
+    /// let config = Config::build(|c| {
+    ///     c.set(|c| c.module_prefix("app"));
+    ///     if verbose {
+    ///         c.set(|c| c.level(Level::Trace)).set(|c| c.quiet_info(true));
+    ///     }
+    /// });
+    /// let description = wasm_logger::describe(&config);
+    /// assert!(description.contains("level: TRACE"));
+    /// assert!(description.contains("module prefix: app"));
+    /// ```
+    pub fn build(f: impl FnOnce(&mut Config)) -> Self {
+        let mut config = Self::default();
+        f(&mut config);
+        config
+    }
+
+    /// Apply the fluent `setter`, such as `|c| c.level(Level::Info)`, to a config held by
+    /// mutable reference, see `Config::build`
+    pub fn set(&mut self, setter: impl FnOnce(Config) -> Config) -> &mut Self {
+        *self = setter(std::mem::take(self));
+        self
+    }
+
+    /// Specify the maximum level you want to log, like `Config::new`
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Replace the level of `Config::new` during some hours of the day, e.g. to log more
+    /// during a maintenance window of a kiosk. Each `(start_hour, end_hour, level)`
+    /// applies from `start_hour` included to `end_hour` excluded, in the local time of the
+    /// browser, and wraps past midnight if `end_hour` is before `start_hour`; the first
+    /// which matches the current hour applies. The level is resolved at `init` and at each
+    /// full hour, not for each entry. The module levels, `boost_level` and the runtime
+    /// filters apply on top of it.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use log::Level;
+    ///
+    /// // Trace from 2:00 to 4:00, warnings only from 22:00 to 6:00 otherwise
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .level_schedule(vec![(2, 4, Level::Trace), (22, 6, Level::Warn)]);
+    /// wasm_logger::init(config);
+    /// ```
+    pub fn level_schedule(mut self, schedule: Vec<(u8, u8, Level)>) -> Self {
+        self.level_schedule = schedule;
+        self
+    }
+
+    /// The level of `Config::level_schedule` at `hour`, if any
+    fn scheduled_level(&self, hour: u8) -> Option<Level> {
+        self.level_schedule
+            .iter()
+            .find(|(start, end, _)| {
+                if start <= end {
+                    (*start..*end).contains(&hour)
+                } else {
+                    hour >= *start || hour < *end
+                }
+            })
+            .map(|(_, _, level)| *level)
+    }
+
     /// Configure timestamp format.
     /// If not configured, timestamp will not be used.
+    ///
+    /// The specifiers of a `TimestampFormat::Custom` are parsed once, here. If they are
+    /// invalid, the entries have no timestamp; `Config::try_timestamp_format` reports them.
     pub fn timestamp_format(mut self, ts_fmt: TimestampFormat) -> Self {
+        self.timestamp_items = match ts_fmt {
+            TimestampFormat::Custom(ref fmt) => chrono::format::StrftimeItems::new(fmt)
+                .parse_to_owned()
+                .ok(),
+            _ => None,
+        };
         self.timestamp_format = Some(ts_fmt);
         self
     }
 
+    /// Like `Config::timestamp_format`, but fail if the specifiers of a
+    /// `TimestampFormat::Custom` are invalid.
+    ///
+    /// ## Errors
+    /// Returns the error of `chrono` for the first invalid specifier.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use wasm_logger::{Config, TimestampFormat};
+    ///
+    /// assert!(Config::default()
+    ///     .try_timestamp_format(TimestampFormat::Custom("%H:%M:%S".to_string()))
+    ///     .is_ok());
+    /// assert!(Config::default()
+    ///     .try_timestamp_format(TimestampFormat::Custom("%H:%Q".to_string()))
+    ///     .is_err());
+    /// ```
+    pub fn try_timestamp_format(self, ts_fmt: TimestampFormat) -> Result<Self, chrono::ParseError> {
+        if let TimestampFormat::Custom(ref fmt) = ts_fmt {
+            chrono::format::StrftimeItems::new(fmt).parse()?;
+        }
+        Ok(self.timestamp_format(ts_fmt))
+    }
+
+    /// Only show the timestamp of `Config::timestamp_format` on the entries of `levels`,
+    /// e.g. to correlate warnings and errors with other events while keeping the debug
+    /// entries compact. If not configured, the timestamp is shown at every level.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Config, TimestampFormat};
+    ///
+    /// let config = Config::new(Level::Trace)
+    ///     .timestamp_format(TimestampFormat::Rfc3339)
+    ///     .timestamp_levels(&[Level::Warn, Level::Error]);
+    /// let record = Record::builder()
+    ///     .args(format_args!("polling"))
+    ///     .level(Level::Debug)
+    ///     .file(Some("src/poll.rs"))
+    ///     .line(Some(7))
+    ///     .build();
+    /// assert_eq!(
+    ///     wasm_logger::render(&config, &record).plain_text(),
+    ///     "DEBUG src/poll.rs:7 polling"
+    /// );
+    /// ```
+    pub fn timestamp_levels(mut self, levels: &[Level]) -> Self {
+        self.timestamp_levels = Some(levels.to_vec());
+        self
+    }
+
+    /// Replace the timestamp of an entry with spaces of the same width if it is the same as
+    /// the timestamp of the previous entry emitted by the logger, e.g. in a burst of entries
+    /// logged within the same millisecond, so that the columns stay aligned without the
+    /// repetition. The entries dropped by `Config::pre_emit` or the other filters do not
+    /// count, and `render` always shows the timestamp. Default to `false`.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use wasm_logger::{Config, TimestampFormat};
+    ///
+    /// let config = Config::new(log::Level::Info)
+    ///     .timestamp_format(TimestampFormat::TimeOnly)
+    ///     .elide_repeated_timestamps(true);
+    /// wasm_logger::init(config);
+    /// // In the same millisecond, the second entry has spaces in place of the timestamp
+    /// log::info!("first");
+    /// log::info!("second");
+    /// ```
+    pub fn elide_repeated_timestamps(mut self, yes: bool) -> Self {
+        self.elide_repeated_timestamps = yes;
+        self
+    }
+
+    /// Configure the number of fractional digits of `TimestampFormat::Performance`,
+    /// at most 6. Default to 3, a precision of microseconds.
+    pub fn performance_precision(mut self, digits: u8) -> Self {
+        self.performance_precision = digits.min(6);
+        self
+    }
+
     /// Configure the `target` of the logger. If specified, the logger
-    /// only output for `log`s in module that its path starts with
-    /// `module_prefix`. wasm-logger only supports single prefix. Only
-    /// the last call to `module_prefix` has effect if you call it multiple times.
+    /// only output for `log`s in module `module_prefix` and its submodules:
+    /// `my_app` matches `my_app` and `my_app::net`, but not `my_app_utils`.
+    /// wasm-logger only supports single prefix. Only the last call to
+    /// `module_prefix` has effect if you call it multiple times.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// wasm_logger::init(wasm_logger::Config::default().module_prefix("my_app"));
+    ///
+    /// let enabled = |target| {
+    ///     let metadata = log::Metadata::builder().level(log::Level::Info).target(target).build();
+    ///     log::logger().enabled(&metadata)
+    /// };
+    /// assert!(enabled("my_app"));
+    /// assert!(enabled("my_app::net"));
+    /// assert!(!enabled("my_app_utils"));
+    /// assert!(!enabled("other::my_app"));
+    /// ```
     pub fn module_prefix(mut self, module_prefix: &str) -> Self {
         self.module_prefix = Some(module_prefix.to_string());
         self
     }
 
+    /// Only output for `log`s whose target is exactly `target`, not its submodules.
+    /// Calling it multiple times adds more targets. If `Config::module_prefix` is also
+    /// configured, a `log` is output if it matches either the prefix or an exact target.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// wasm_logger::init(
+    ///     wasm_logger::Config::default()
+    ///         .module_prefix("app::ui")
+    ///         .exact_target("app::net"),
+    /// );
+    ///
+    /// let enabled = |target| {
+    ///     let metadata = log::Metadata::builder().level(log::Level::Info).target(target).build();
+    ///     log::logger().enabled(&metadata)
+    /// };
+    /// assert!(enabled("app::net"));
+    /// assert!(!enabled("app::net::http"));
+    /// assert!(enabled("app::ui::button"));
+    /// assert!(!enabled("app"));
+    /// ```
+    pub fn exact_target(mut self, target: &str) -> Self {
+        self.exact_targets.push(target.to_string());
+        self
+    }
+
+    /// Set the maximum level of the `log`s whose target is `target` or one of its submodules,
+    /// instead of the level of `Config::new`. If several rules match a target, the one with
+    /// the longest `target` applies. `boost_level` overrides these rules while it is active.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::LevelFilter;
+    ///
+    /// let config = wasm_logger::Config::new(log::Level::Info)
+    ///     .module_level("my_app::net", LevelFilter::Trace)
+    ///     .module_level("hyper", LevelFilter::Off);
+    /// ```
+    pub fn module_level(mut self, target: &str, level: LevelFilter) -> Self {
+        self.module_levels.push((target.to_string(), level));
+        self
+    }
+
+    /// Always output the `log`s whose target is `target` or one of its submodules, at any
+    /// level and regardless of the target rules, e.g. for a health module which must be
+    /// heard even when the level is lowered to debug something else. Calling it multiple
+    /// times adds more targets. A `Config::module_level` of `LevelFilter::Off` still turns
+    /// the matching targets off, and `shutdown` turns everything off.
+    ///
+    /// This keeps `log::max_level` at trace, so the `log` macros of every target reach the
+    /// logger to be filtered there.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, LevelFilter, Log, Metadata};
+    ///
+    /// wasm_logger::init(
+    ///     wasm_logger::Config::new(Level::Error)
+    ///         .module_prefix("app")
+    ///         .always_on_target("health")
+    ///         .module_level("health::noisy", LevelFilter::Off),
+    /// );
+    /// let enabled = |level, target| {
+    ///     log::logger().enabled(&Metadata::builder().level(level).target(target).build())
+    /// };
+    /// assert!(enabled(Level::Trace, "health::probe"));
+    /// assert!(!enabled(Level::Error, "health::noisy"));
+    /// assert!(!enabled(Level::Debug, "app"));
+    /// assert!(enabled(Level::Error, "app"));
+    /// ```
+    pub fn always_on_target(mut self, target: &str) -> Self {
+        self.always_on_targets.push(target.to_string());
+        self
+    }
+
+    /// Apply the comma-separated directives of `directives`, in the syntax of `RUST_LOG`.
+    /// The supported subset of the `env_logger` syntax is:
+    /// - `level`, e.g. `info`: the level of the entries which no other directive matches,
+    ///   as `Config::new`. `off` turns them off.
+    /// - `target=level`, e.g. `my_app::net=trace`: as `Config::module_level`.
+    /// - `target`, e.g. `my_app`: all the entries of `target`, as `target=trace`.
+    ///
+    /// Levels are case-insensitive and whitespace around the directives is ignored.
+    /// The `/regex` message filters are not supported.
+    ///
+    /// ## Errors
+    /// If a directive is malformed, e.g. `app=loud` or `=info`, the directives are not
+    /// applied and the error holds the first malformed directive.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Log, Metadata};
+    ///
+    /// let config = wasm_logger::Config::default()
+    ///     .parse_directives("info,my_app::net=trace,hyper=off")
+    ///     .unwrap();
+    /// wasm_logger::init(config);
+    ///
+    /// let enabled = |level, target| {
+    ///     log::logger().enabled(&Metadata::builder().level(level).target(target).build())
+    /// };
+    /// assert!(enabled(Level::Trace, "my_app::net::http"));
+    /// assert!(!enabled(Level::Debug, "my_app"));
+    /// assert!(enabled(Level::Info, "my_app"));
+    /// assert!(!enabled(Level::Error, "hyper"));
+    ///
+    /// assert!(wasm_logger::Config::default().parse_directives("app=loud").is_err());
+    /// ```
+    pub fn parse_directives(mut self, directives: &str) -> Result<Self, DirectiveError> {
+        let invalid = |directive: &str| DirectiveError {
+            directive: directive.to_string(),
+        };
+        for directive in directives.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+            let mut parts = directive.split('=');
+            let (target, level) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(part), None, None) => match part.parse::<LevelFilter>() {
+                    Ok(level) => (None, level),
+                    Err(_) => (Some(part), LevelFilter::Trace),
+                },
+                (Some(target), Some(level), None) => match level.trim().parse::<LevelFilter>() {
+                    Ok(level) => (Some(target.trim()), level),
+                    Err(_) => return Err(invalid(directive)),
+                },
+                _ => return Err(invalid(directive)),
+            };
+            match (target, level.to_level()) {
+                (Some(target), _) if target.is_empty() || target.contains('/') => {
+                    return Err(invalid(directive))
+                }
+                (Some(target), _) => self.module_levels.push((target.to_string(), level)),
+                (None, Some(level)) => self.level = level,
+                // `Config::level` can not be off, a rule which matches every target can
+                (None, None) => self.module_levels.push((String::new(), LevelFilter::Off)),
+            }
+        }
+        Ok(self)
+    }
+
     /// Put the message on a new line, separated from other information
     /// such as level, file path, line number.
     pub fn message_on_new_line(mut self) -> Self {
         self.message_location = MessageLocation::NewLine;
         self
     }
-}
 
-/// The log styles
-struct Style {
-    lvl_trace: String,
-    lvl_debug: String,
-    lvl_info: String,
-    lvl_warn: String,
-    lvl_error: String,
-    tgt: String,
-    args: String,
-}
+    /// Configure how the level is displayed. Default to `LevelDisplay::Full`.
+    pub fn level_display(mut self, level_display: LevelDisplay) -> Self {
+        self.level_display = level_display;
+        self
+    }
 
-impl Style {
-    fn new() -> Style {
-        let base = String::from("color: white; padding: 0 3px; background:");
-        Style {
-            lvl_trace: format!("{} gray;", base),
-            lvl_debug: format!("{} blue;", base),
-            lvl_info: format!("{} green;", base),
-            lvl_warn: format!("{} orange;", base),
-            lvl_error: format!("{} darkred;", base),
-            tgt: String::from("font-weight: bold; color: inherit"),
-            args: String::from("background: inherit; color: inherit"),
-        }
+    /// Configure the case of the level label, with any `LevelDisplay`. Default to
+    /// `Case::Upper`. The level of the JSON layouts is not affected.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Case, Config};
+    ///
+    /// let config = Config::default().level_case(Case::Title);
+    /// let record = Record::builder().level(Level::Warn).args(format_args!("low disk")).build();
+    /// assert!(wasm_logger::render(&config, &record).text.starts_with("%cWarn"));
+    /// ```
+    pub fn level_case(mut self, case: Case) -> Self {
+        self.level_case = case;
+        self
     }
-}
 
-/// The logger
-struct WasmLogger {
-    config: Config,
-    style: Style,
-}
+    /// Put an emoji of the severity before each entry, in addition to its level:
+    /// ❌ for errors, ⚠️ for warnings, ℹ️ for info, 🐛 for debug and 🔍 for trace entries.
+    /// See `Config::level_emoji` to pick other emojis.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Info).level_emoji_prefix(true);
+    /// let record = Record::builder()
+    ///     .args(format_args!("disk full"))
+    ///     .level(Level::Error)
+    ///     .file(Some("src/store.rs"))
+    ///     .line(Some(42))
+    ///     .build();
+    /// assert_eq!(
+    ///     wasm_logger::render(&config, &record).plain_text(),
+    ///     "❌ ERROR src/store.rs:42 disk full"
+    /// );
+    /// ```
+    pub fn level_emoji_prefix(mut self, yes: bool) -> Self {
+        self.level_emoji_prefix = yes;
+        self
+    }
 
-impl Log for WasmLogger {
-    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
-        if let Some(ref prefix) = self.config.module_prefix {
-            metadata.target().starts_with(prefix)
-        } else {
-            true
-        }
+    /// Replace the emoji of `level` for `Config::level_emoji_prefix`
+    pub fn level_emoji(mut self, level: Level, emoji: &str) -> Self {
+        self.level_emojis[level as usize - 1] = emoji.to_string();
+        self
     }
 
-    fn log(&self, record: &Record<'_>) {
-        if self.enabled(record.metadata()) {
-            let style = &self.style;
-            let message_separator = match self.config.message_location {
-                MessageLocation::NewLine => "\n",
-                MessageLocation::SameLine => " ",
-            };
-            let timestamp = self
-                .config
-                .timestamp_format
-                .as_ref()
-                .map(|ts_fmt| {
-                    chrono::NaiveDateTime::from_timestamp_millis(js_sys::Date::now() as i64)
-                        .map(|ndt| chrono::DateTime::<chrono::Utc>::from_utc(ndt, chrono::Utc))
-                        .map(|dt| match ts_fmt {
-                            TimestampFormat::Rfc2822 => dt.to_rfc2822(),
-                            TimestampFormat::Rfc3339 => dt.to_rfc3339(),
-                            TimestampFormat::Custom(fmt) => dt.format(&fmt).to_string(),
-                        })
-                })
-                .flatten()
-                .map_or("".to_string(), |s| format!("{s} "));
-            let s = format!(
-                "%c{}%c {}{}:{}%c{}{}",
-                record.level(),
-                timestamp,
-                record.file().unwrap_or_else(|| record.target()),
-                record
-                    .line()
-                    .map_or_else(|| "[Unknown]".to_string(), |line| line.to_string()),
-                message_separator,
-                record.args(),
-            );
-            let s = JsValue::from_str(&s);
-            let tgt_style = JsValue::from_str(&style.tgt);
-            let args_style = JsValue::from_str(&style.args);
-
-            match record.level() {
-                Level::Trace => console::debug_4(
-                    &s,
-                    &JsValue::from(&style.lvl_trace),
-                    &tgt_style,
-                    &args_style,
-                ),
-                Level::Debug => console::log_4(
-                    &s,
-                    &JsValue::from(&style.lvl_debug),
-                    &tgt_style,
-                    &args_style,
-                ),
-                Level::Info => {
-                    console::info_4(&s, &JsValue::from(&style.lvl_info), &tgt_style, &args_style)
-                }
-                Level::Warn => {
-                    console::warn_4(&s, &JsValue::from(&style.lvl_warn), &tgt_style, &args_style)
-                }
-                Level::Error => console::error_4(
-                    &s,
-                    &JsValue::from(&style.lvl_error),
-                    &tgt_style,
-                    &args_style,
-                ),
-            }
-        }
+    /// Configure the text inserted before the message when it is on its own line
+    /// (see `Config::message_on_new_line`), e.g. `"    "` to indent it or `"→ "` to mark it.
+    /// Default to an empty string.
+    pub fn newline_indent(mut self, indent: &str) -> Self {
+        self.newline_indent = indent.to_string();
+        self
     }
 
-    fn flush(&self) {}
-}
+    /// Insert `prefix` before the message of each entry, e.g. the short name of the app when
+    /// several wasm modules share the console. It is inserted as is, not as a template, and
+    /// only in the human-readable layouts. Default to an empty string.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().message_prefix(">> ").message_suffix(" <<");
+    /// let rendered = wasm_logger::render(
+    ///     &config,
+    ///     &Record::builder().level(Level::Info).args(format_args!("ready")).build(),
+    /// );
+    /// assert!(rendered.plain_text().ends_with(" >> ready <<"));
+    /// ```
+    pub fn message_prefix(mut self, prefix: &str) -> Self {
+        self.message_prefix = prefix.to_string();
+        self
+    }
 
-/// Initialize the logger which the given config. If failed, it will log a message to the the browser console.
-///
-/// ## Examples
-/// ```rust
-/// wasm_logger::init(wasm_logger::Config::new(log::Level::Debug));
-/// ```
-/// or
-/// ```rust
-/// wasm_logger::init(wasm_logger::Config::with_prefix(log::Level::Debug, "some::module"));
-/// ```
-pub fn init(config: Config) {
-    let max_level = config.level;
-    let wl = WasmLogger {
-        config,
-        style: Style::new(),
-    };
+    /// Append `suffix` to the message of each entry, like `Config::message_prefix`.
+    /// Default to an empty string.
+    pub fn message_suffix(mut self, suffix: &str) -> Self {
+        self.message_suffix = suffix.to_string();
+        self
+    }
+
+    /// Configure the number of spaces inserted before the message for each level of
+    /// `indent`, in the human-readable layouts. Default to 2.
+    pub fn indent_width(mut self, width: usize) -> Self {
+        self.indent_width = width;
+        self
+    }
+
+    /// Add the field `key` with `value` to every entry, e.g. `app_version` or `env`, for the
+    /// lifetime of the logger. Calling it multiple times adds more fields. They follow the
+    /// message as `[key=value ...]`, unless `Config::show_global_fields` is `false`, and are
+    /// always in the structured outputs: the `fields` of the JSON layouts, the metadata of
+    /// `Config::rich_grouping` and the entries sent to `Config::remote_url`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default()
+    ///     .global_field("app_version", "1.4.2")
+    ///     .global_field("env", "staging");
+    /// let rendered = wasm_logger::render(
+    ///     &config,
+    ///     &Record::builder().level(Level::Info).args(format_args!("ready")).build(),
+    /// );
+    /// assert!(rendered.plain_text().ends_with(" ready [app_version=1.4.2 env=staging]"));
+    /// ```
+    pub fn global_field(mut self, key: &str, value: &str) -> Self {
+        self.global_fields
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Whether the console entries of the human-readable layouts show the
+    /// `Config::global_field`s. Default to `true`. The structured outputs always have them.
+    pub fn show_global_fields(mut self, yes: bool) -> Self {
+        self.show_global_fields = yes;
+        self
+    }
+
+    /// Replace each whitespace-delimited token of the messages which is longer than
+    /// `max_token_len` characters, e.g. a data URI or a base64 blob, with `<token:LEN chars>`,
+    /// and keep the text around it. Unlike `Config::max_message_len`, the rest of a long
+    /// message is kept. By default, tokens are kept whole.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().collapse_long_tokens(16);
+    /// let rendered = wasm_logger::render(
+    ///     &config,
+    ///     &Record::builder()
+    ///         .level(Level::Debug)
+    ///         .args(format_args!("loaded data:image/png;base64,iVBORw0KGgo= as logo"))
+    ///         .build(),
+    /// );
+    /// assert!(rendered.plain_text().ends_with(" loaded <token:34 chars> as logo"));
+    /// ```
+    pub fn collapse_long_tokens(mut self, max_token_len: usize) -> Self {
+        self.max_token_len = Some(max_token_len);
+        self
+    }
+
+    /// Replace the control characters of the messages, other than `\n` and `\t`, with
+    /// their escaped form, e.g. `\x1b` for the escape of an ANSI sequence, so that binary
+    /// data or a crafted input cannot garble the output or inject terminal escapes into
+    /// logs copied to a terminal. The JSON layouts always escape them. Default to `true`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let record = |config: &Config| {
+    ///     wasm_logger::render(
+    ///         config,
+    ///         &Record::builder()
+    ///             .level(Level::Info)
+    ///             .args(format_args!("user: \x1b[31mroot\x1b[0m\r\x07"))
+    ///             .build(),
+    ///     )
+    ///     .plain_text()
+    /// };
+    /// assert!(record(&Config::default()).ends_with(r" user: \x1b[31mroot\x1b[0m\x0d\x07"));
+    /// let raw = Config::default().sanitize_control_chars(false);
+    /// assert!(record(&raw).ends_with(" user: \x1b[31mroot\x1b[0m\r\x07"));
+    /// ```
+    pub fn sanitize_control_chars(mut self, yes: bool) -> Self {
+        self.sanitize_control_chars = yes;
+        self
+    }
+
+    /// Configure how the key-values shown after the message, such as the
+    /// `Config::global_field`s, are written: `pair_sep` joins the pairs and `kv_sep` separates
+    /// each key from its value. Default to `" "` and `"="`, as in logfmt. The JSON layouts
+    /// and the metadata of `Config::rich_grouping` keep their own format.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default()
+    ///     .global_field("app", "shop")
+    ///     .global_field("env", "prod")
+    ///     .global_field("region", "eu")
+    ///     .kv_format(", ", ": ");
+    /// let rendered = wasm_logger::render(
+    ///     &config,
+    ///     &Record::builder().level(Level::Info).args(format_args!("ready")).build(),
+    /// );
+    /// assert!(rendered.plain_text().ends_with(" ready [app: shop, env: prod, region: eu]"));
+    /// ```
+    pub fn kv_format(mut self, pair_sep: &str, kv_sep: &str) -> Self {
+        self.kv_pair_separator = pair_sep.to_string();
+        self.kv_separator = kv_sep.to_string();
+        self
+    }
+
+    /// Truncate the messages longer than `max_len` characters to `max_len` characters,
+    /// and mark the cut with `Config::truncation_indicator`. `Config::truncate_mode` picks
+    /// the part which is kept. By default, messages are not truncated.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Config, TruncateMode};
+    ///
+    /// let config = Config::default()
+    ///     .max_message_len(8)
+    ///     .truncate_mode(TruncateMode::Middle)
+    ///     .truncation_indicator("[...]");
+    /// let record = Record::builder()
+    ///     .args(format_args!("request failed: E1042"))
+    ///     .level(Level::Error)
+    ///     .build();
+    /// let rendered = wasm_logger::render(&config, &record);
+    /// assert!(rendered.plain_text().ends_with(" requ[...]1042"));
+    /// ```
+    pub fn max_message_len(mut self, max_len: usize) -> Self {
+        self.max_message_len = Some(max_len);
+        self
+    }
+
+    /// Configure which part of the messages longer than `Config::max_message_len` is kept.
+    /// Default to `TruncateMode::Head`.
+    pub fn truncate_mode(mut self, mode: TruncateMode) -> Self {
+        self.truncate_mode = mode;
+        self
+    }
+
+    /// Configure the text which replaces the part cut by `Config::max_message_len`.
+    /// Default to `…`.
+    pub fn truncation_indicator(mut self, indicator: &str) -> Self {
+        self.truncation_indicator = indicator.to_string();
+        self
+    }
+
+    /// Indent the continuation lines of a multi-line message so that they align
+    /// under the first line of the message instead of starting at column zero.
+    pub fn indent_continuation(mut self, indent: bool) -> Self {
+        self.indent_continuation = indent;
+        self
+    }
+
+    /// Configure what is shown in place of the file path when a record has no file,
+    /// e.g. records that do not come from the `log!` macros. Default to `LocationFallback::Target`.
+    pub fn location_fallback(mut self, fallback: LocationFallback) -> Self {
+        self.location_fallback = fallback;
+        self
+    }
+
+    /// Show `location`, e.g. `<js>` or the name of the app, in place of the file path of the
+    /// records which have no file, such as the records of `emit`, so that they stand out
+    /// from the records of real call sites. The line is shown as usual, `[Unknown]` if the
+    /// record has none. A shorthand for `LocationFallback::Synthetic`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Info).synthetic_location("<js>");
+    /// let record = Record::builder()
+    ///     .args(format_args!("clicked"))
+    ///     .level(Level::Info)
+    ///     .target("js::bridge")
+    ///     .build();
+    /// assert_eq!(
+    ///     wasm_logger::render(&config, &record).plain_text(),
+    ///     "INFO <js>:[Unknown] clicked"
+    /// );
+    /// ```
+    pub fn synthetic_location(mut self, location: &str) -> Self {
+        self.location_fallback = LocationFallback::Synthetic(location.to_string());
+        self
+    }
+
+    /// Configure what is shown in place of the target of the records whose target is empty,
+    /// e.g. records built by hand, and which have no module path either. Default to
+    /// `<unknown>`. The records with an empty target otherwise show their module path.
+    ///
+    /// Records with an empty target only pass the target rules if neither
+    /// `Config::module_prefix` nor `Config::exact_target` is configured.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Log, Metadata, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .env_logger_style()
+    ///     .timestamp_levels(&[])
+    ///     .empty_target_placeholder("<js>");
+    /// let record = Record::builder().args(format_args!("clicked")).level(Level::Info).build();
+    /// assert_eq!(wasm_logger::render(&config, &record).plain_text(), "[INFO  <js>] clicked");
+    ///
+    /// wasm_logger::init(wasm_logger::Config::new(Level::Info).module_prefix("app"));
+    /// let metadata = Metadata::builder().level(Level::Info).target("").build();
+    /// assert!(!log::logger().enabled(&metadata));
+    /// ```
+    pub fn empty_target_placeholder(mut self, placeholder: &str) -> Self {
+        self.empty_target_placeholder = placeholder.to_string();
+        self
+    }
+
+    /// Configure what is done with the records whose message is empty. Default to
+    /// `EmptyMessagePolicy::Show`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Config, EmptyMessagePolicy};
+    ///
+    /// let record = Record::builder()
+    ///     .level(Level::Trace)
+    ///     .file(Some("src/bridge.rs"))
+    ///     .line(Some(7))
+    ///     .args(format_args!(""))
+    ///     .build();
+    /// let rendered = wasm_logger::render(&Config::default(), &record);
+    /// assert_eq!(rendered.plain_text(), "TRACE src/bridge.rs:7 ");
+    ///
+    /// let config = Config::default()
+    ///     .empty_message(EmptyMessagePolicy::Placeholder("<no message>".to_string()));
+    /// let rendered = wasm_logger::render(&config, &record);
+    /// assert_eq!(rendered.plain_text(), "TRACE src/bridge.rs:7 <no message>");
+    /// ```
+    pub fn empty_message(mut self, policy: EmptyMessagePolicy) -> Self {
+        self.empty_message = policy;
+        self
+    }
+
+    /// Cap the total number of entries logged per second, whatever their level or message.
+    /// Entries over the budget are dropped, and the number of dropped entries is reported
+    /// once the budget allows logging again.
+    pub fn max_logs_per_second(mut self, max: u32) -> Self {
+        self.max_logs_per_second = Some(max);
+        self
+    }
+
+    /// Stop logging after `max` entries, e.g. so that an infinite loop does not hang the
+    /// tab with millions of console lines. Once the limit is reached, a single
+    /// `wasm-logger: output limit reached, logging paused` diagnostic is logged with
+    /// `Config::internal_log_method`, and the entries are dropped until `resume_logging`.
+    /// Unlike `Config::max_logs_per_second`, which caps the rate, this caps the total.
+    /// By default, there is no limit.
+    pub fn max_total_logs(mut self, max: usize) -> Self {
+        self.max_total_logs = Some(max);
+        self
+    }
+
+    /// Collapse the consecutive entries which have the same level, target and message:
+    /// only the first one is logged, and the number of repeats is reported with the method
+    /// of `Config::internal_log_method` when a different entry is logged or the logger
+    /// is flushed, as `[previous entry repeated N times]`. The repeats are counted in the
+    /// `deduped` of `suppression_stats`.
+    pub fn dedup_consecutive(mut self, yes: bool) -> Self {
+        self.dedup_consecutive = yes;
+        self
+    }
+
+    /// Collapse the consecutive entries like `Config::dedup_consecutive`, which it turns
+    /// on, but compare their messages after `normalize_message`, so that `user 1 connected`
+    /// and `user 2 connected` are repeats. The first entry is shown with its own message.
+    pub fn dedup_normalized(mut self, yes: bool) -> Self {
+        self.dedup_normalized = yes;
+        self
+    }
+
+    /// Start the entries logged inside a `with_context` scope with the names of the running
+    /// scopes, styled with `css`, e.g. `border-left: 3px solid purple; padding-left: 4px`, so
+    /// that the entries of a tracked operation stand out from the others. By default, the
+    /// scopes are not shown.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().style_contextual("border-left: 3px solid purple");
+    /// let render = || {
+    ///     let record = Record::builder()
+    ///         .args(format_args!("paid"))
+    ///         .level(Level::Info)
+    ///         .build();
+    ///     wasm_logger::render(&config, &record)
+    /// };
+    /// assert!(render().text.starts_with("%cINFO"));
+    /// wasm_logger::with_context("checkout", || {
+    ///     wasm_logger::with_context("payment", || {
+    ///         let rendered = render();
+    ///         assert!(rendered.text.starts_with("%ccheckout › payment %cINFO"));
+    ///         assert_eq!(rendered.styles[0], "border-left: 3px solid purple");
+    ///     })
+    /// });
+    /// ```
+    pub fn style_contextual(mut self, css: &str) -> Self {
+        self.contextual_style = Some(css.to_string());
+        self
+    }
+
+    /// Open a `console.group` named after each `with_context` or `span` scope when it is
+    /// entered, and close it when it is left, so that the console nests the entries like
+    /// the scopes which logged them.
+    ///
+    /// The logger keeps the stack of the scopes whose group is open. When a scope is
+    /// entered or left, the groups of the scopes which are no longer running, and the
+    /// groups nested in them, are closed innermost first, and the groups of the running
+    /// scopes which have none are opened; so that the groups stay paired even if a scope
+    /// is left before a scope nested in it. The group of `Config::auto_group_by_target`,
+    /// if any, is closed first. `shutdown` closes the groups which are still open.
+    pub fn group_from_context(mut self, yes: bool) -> Self {
+        self.group_from_context = yes;
+        self
+    }
+
+    /// By default, `log::debug!` calls `console.log` and `log::trace!` calls `console.debug`.
+    /// If `true`, `log::debug!` calls `console.debug` too, so that browsers hide debug
+    /// entries together with trace entries unless their verbose filter is on.
+    pub fn debug_uses_console_debug(mut self, yes: bool) -> Self {
+        self.debug_uses_console_debug = yes;
+        self
+    }
+
+    /// By default, `log::info!` calls `console.info`, which some browsers decorate with an
+    /// icon or a background. If `true`, `log::info!` calls `console.log` instead.
+    pub fn quiet_info(mut self, yes: bool) -> Self {
+        self.quiet_info = yes;
+        self
+    }
+
+    /// If `true`, `log::trace!` calls `console.trace` instead of `console.debug`, so that
+    /// each trace entry comes with the JavaScript stack trace, including the frames of the
+    /// generated glue code.
+    pub fn trace_with_stack(mut self, yes: bool) -> Self {
+        self.trace_with_stack = yes;
+        self
+    }
+
+    /// If `true` and the app runs in Node, e.g. for server-side rendering or a CLI, log the
+    /// warn and error entries with `console.error`, which writes to stderr, and the other
+    /// ones with `console.log`, which writes to stdout, so that the hosting process can
+    /// capture them apart, as with `2>`. This takes precedence over `Config::quiet_info`,
+    /// `Config::debug_uses_console_debug`, `Config::trace_with_stack` and
+    /// `Config::severity_override`, but not over the method of `log_as`, and does not apply
+    /// to the groups of `Config::rich_grouping`. In a browser, this has no effect.
+    pub fn unix_streams(mut self, yes: bool) -> Self {
+        self.unix_streams = yes;
+        self
+    }
+
+    /// Color and log the entries of `level` as `severity`, e.g. `Severity::High` to make the
+    /// info entries of an app stand out like warnings. This takes precedence over
+    /// `Config::quiet_info`, `Config::debug_uses_console_debug` and
+    /// `Config::trace_with_stack` for `level`. Without an override, each level keeps its
+    /// own color and console method.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Config, Severity};
+    ///
+    /// let config = Config::default().severity_override(Level::Info, Severity::High);
+    /// let record = |level| {
+    ///     let record = Record::builder().args(format_args!("")).level(level).build();
+    ///     wasm_logger::render(&config, &record)
+    /// };
+    /// assert_eq!(record(Level::Info).styles[0], record(Level::Warn).styles[0]);
+    /// ```
+    pub fn severity_override(mut self, level: Level, severity: Severity) -> Self {
+        self.severity_overrides
+            .retain(|(overridden, _)| *overridden != level);
+        self.severity_overrides.push((level, severity));
+        self
+    }
+
+    /// Configure how levels are colored. Default to `StyleMode::Badge`.
+    pub fn style_mode(mut self, mode: StyleMode) -> Self {
+        self.style_mode = mode;
+        self
+    }
+
+    /// Color the level and the location with ANSI escape sequences instead of CSS, and log
+    /// each entry as a single string without `%c` directives, for the test runners and the
+    /// Node processes whose console output goes to a terminal. The levels keep their colors,
+    /// as a background with `StyleMode::Badge` or as bold text with `StyleMode::Text`, and
+    /// `Config::severity_override` still applies.
+    ///
+    /// This takes precedence over all the CSS styling, such as `Config::highlight`,
+    /// `Config::target_color` or a `#` in the target, which is not applied. It does not
+    /// apply to `Config::json_format`, `Config::json_lite` and `Config::logfmt`. The outputs
+    /// which get the text of the entries, such as `Config::remote_url`, get the escape
+    /// sequences too.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().ansi_colors(true);
+    /// let record = Record::builder()
+    ///     .level(Level::Warn)
+    ///     .file(Some("src/main.rs"))
+    ///     .line(Some(3))
+    ///     .args(format_args!("low disk"))
+    ///     .build();
+    /// let rendered = wasm_logger::render(&config, &record);
+    /// assert_eq!(rendered.text, "\x1b[97;43mWARN\x1b[0m\x1b[1m src/main.rs:3\x1b[0m low disk");
+    /// assert!(rendered.styles.is_empty());
+    /// ```
+    pub fn ansi_colors(mut self, yes: bool) -> Self {
+        self.ansi_colors = yes;
+        self
+    }
+
+    /// Make the level colors translucent, from `0.0` (invisible) to `1.0` (opaque, default),
+    /// so that the row tint of `console.warn` and `console.error` shows through the badges.
+    /// It applies to the text color with `StyleMode::Text`. Values out of range are clamped.
+    /// The colors are blended with the CSS function `color-mix`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Info).badge_opacity(0.5);
+    /// let record = Record::builder().args(format_args!("ready")).level(Level::Info).build();
+    /// let rendered = wasm_logger::render(&config, &record);
+    /// assert!(rendered.styles[0].contains("color-mix(in srgb, green 50%, transparent)"));
+    /// ```
+    pub fn badge_opacity(mut self, opacity: f32) -> Self {
+        self.badge_opacity = if opacity.is_nan() {
+            1.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Replace the level colors with a gradient derived from the severity of the levels:
+    /// the hue goes from blue for trace to green for info and red for error, and the color
+    /// gets more saturated and darker as the severity grows. `base_hue_shift` rotates the
+    /// whole gradient by that many degrees on the color wheel, `0.0` keeps it as described.
+    /// The colors are lighter with `Config::adapt_to_color_scheme` and a dark theme.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Trace).severity_gradient(0.0);
+    /// let record = |level| {
+    ///     let record = Record::builder().args(format_args!("")).level(level).build();
+    ///     wasm_logger::render(&config, &record)
+    /// };
+    /// assert!(record(Level::Error).styles[0].contains("hsl(0, 80%, 35%)"));
+    /// assert!(record(Level::Info).styles[0].contains("hsl(104, 55%, 43%)"));
+    /// ```
+    pub fn severity_gradient(mut self, base_hue_shift: f32) -> Self {
+        self.severity_gradient = if base_hue_shift.is_finite() {
+            Some(base_hue_shift)
+        } else {
+            Some(0.0)
+        };
+        self
+    }
+
+    /// Remove `prefix` from the start of file paths before displaying them, e.g.
+    /// `strip_path_prefix("/home/user/project/src/")` shows `net/client.rs:42`.
+    /// Calling it multiple times adds more prefixes, the longest matching one is removed.
+    pub fn strip_path_prefix(mut self, prefix: &str) -> Self {
+        self.strip_path_prefixes.push(prefix.to_string());
+        self
+    }
+
+    /// Pick level colors suited to the light or dark theme of the browser, according to
+    /// `prefers-color-scheme`, and follow later changes of the theme. Default to `false`,
+    /// which keeps the same colors whatever the theme.
+    pub fn adapt_to_color_scheme(mut self, yes: bool) -> Self {
+        self.adapt_to_color_scheme = yes;
+        self
+    }
+
+    /// Color the location of each entry with a color derived from the target of the record,
+    /// so that the entries of different modules are easy to tell apart. A target always
+    /// gets the same color.
+    pub fn color_by_target(mut self, yes: bool) -> Self {
+        self.color_by_target = yes;
+        self
+    }
+
+    /// Start each entry with the tag `seed`, in a color derived from it, so that the entries
+    /// of several wasm modules or micro-frontends of a page are easy to tell apart in the
+    /// shared console: each module sets its own seed, e.g. its name, and a seed always gets
+    /// the same color. By default, the entries have no tag.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().instance_color("checkout");
+    /// let record = Record::builder().level(Level::Info).args(format_args!("ready")).build();
+    /// let rendered = wasm_logger::render(&config, &record);
+    /// assert!(rendered.text.starts_with("%ccheckout %cINFO"));
+    /// assert_eq!(rendered.styles[0], "font-weight: bold; color: hsl(121, 65%, 45%)");
+    /// ```
+    pub fn instance_color(mut self, seed: &str) -> Self {
+        self.instance_tag = Some(seed.to_string());
+        self
+    }
+
+    /// Start each entry with the frame counter of `next_frame`, as in `[f:1234] INFO ...`,
+    /// to tell which frame of a game or an animation loop logged it. By default, the
+    /// frame is not shown.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().show_frame(true);
+    /// let render = || {
+    ///     let record = Record::builder().level(Level::Debug).args(format_args!("jump")).build();
+    ///     wasm_logger::render(&config, &record).text
+    /// };
+    /// assert!(render().starts_with("[f:0] %cDEBUG"));
+    /// wasm_logger::next_frame();
+    /// assert!(render().starts_with("[f:1] %cDEBUG"));
+    /// ```
+    pub fn show_frame(mut self, yes: bool) -> Self {
+        self.show_frame = yes;
+        self
+    }
+
+    /// Configure the character of the lines of `separator` and `separator_labeled`.
+    /// Default to `─`.
+    pub fn separator_char(mut self, c: char) -> Self {
+        self.separator_char = c;
+        self
+    }
+
+    /// Configure the width, in characters, of the lines of `separator` and
+    /// `separator_labeled`, including the label. Default to 40.
+    pub fn separator_width(mut self, width: usize) -> Self {
+        self.separator_width = width;
+        self
+    }
+
+    /// Configure the level at which `separator` and `separator_labeled` log their lines.
+    /// Default to `Level::Info`.
+    pub fn separator_level(mut self, level: Level) -> Self {
+        self.separator_level = level;
+        self
+    }
+
+    /// Apply `css`, e.g. `"color: purple"`, to the location of the entries of the module
+    /// `prefix` and its submodules. Calling it multiple times adds more rules, the longest
+    /// matching prefix is used. It takes precedence over `Config::color_by_target`.
+    pub fn target_color(mut self, prefix: &str, css: &str) -> Self {
+        self.target_colors
+            .push((prefix.to_string(), css.to_string()));
+        self
+    }
+
+    /// Style the message of the entries which match `regex` with `css`, instead of the
+    /// default message style, e.g. `background: khaki` to make `DEPRECATED` notices stand
+    /// out at any level. Calling it multiple times adds more rules; the first rule which
+    /// matches applies.
+    ///
+    /// Requires the `regex` feature, which adds a dependency on the `regex` crate.
+    ///
+    /// ## Errors
+    /// Returns the error of `regex::Regex::new` if `regex` is invalid.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # #[cfg(feature = "regex")]
+    /// # {
+    /// use log::{Level, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .highlight(r"\bDEPRECATED\b", "background: khaki")
+    ///     .unwrap();
+    /// let record = Record::builder()
+    ///     .args(format_args!("DEPRECATED: use `load_v2`"))
+    ///     .level(Level::Warn)
+    ///     .build();
+    /// let rendered = wasm_logger::render(&config, &record);
+    /// assert_eq!(rendered.styles.last().unwrap(), "background: khaki");
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn highlight(mut self, regex: &str, css: &str) -> Result<Self, regex::Error> {
+        self.highlights
+            .push((regex::Regex::new(regex)?, css.to_string()));
+        Ok(self)
+    }
+
+    /// Output the `log`s whose target matches `regex` up to `level`, e.g.
+    /// `^(app|ui)::` for several crates of a workspace. Calling it multiple times adds more
+    /// regexes, which are tried in order: the first one which matches the target gives the
+    /// level of the record, and lets it through the target rules such as
+    /// `Config::module_prefix`. The targets which match no regex fall back to the
+    /// `Config::module_level`s, then to the level of `Config::new`.
+    ///
+    /// The syntax is the one of the `regex` crate, which has no look-around: a pattern such
+    /// as `^app::(?!generated)` can be written as an earlier regex for `^app::generated`
+    /// with a lower level. Like `Config::module_level`, this keeps `log::max_level` at the
+    /// most verbose of the levels so that the records reach the logger.
+    ///
+    /// Requires the `regex` feature.
+    ///
+    /// ## Errors
+    /// Returns the error of `regex::Regex::new` if `regex` is invalid.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # #[cfg(feature = "regex")]
+    /// # {
+    /// use log::{Level, Log, Metadata};
+    ///
+    /// wasm_logger::init(
+    ///     wasm_logger::Config::new(Level::Warn)
+    ///         .module_prefix("app")
+    ///         .target_regex(r"^app::generated(::|$)", Level::Error)
+    ///         .unwrap()
+    ///         .target_regex(r"^(app|ui)::", Level::Debug)
+    ///         .unwrap(),
+    /// );
+    /// let enabled = |level, target| {
+    ///     log::logger().enabled(&Metadata::builder().level(level).target(target).build())
+    /// };
+    /// assert!(enabled(Level::Debug, "ui::button"));
+    /// assert!(!enabled(Level::Warn, "app::generated::schema"));
+    /// assert!(!enabled(Level::Info, "app"));
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn target_regex(mut self, regex: &str, level: Level) -> Result<Self, regex::Error> {
+        self.target_regexes
+            .push((regex::Regex::new(regex)?, level.to_level_filter()));
+        Ok(self)
+    }
+
+    /// Cap the number of independently styled (`%c`) segments of an entry. If an entry
+    /// has more segments, the remaining ones are logged as unstyled text, which keeps
+    /// long entries from hitting the limits of browser consoles. Default to 8.
+    pub fn max_style_segments(mut self, max: usize) -> Self {
+        self.max_style_segments = max;
+        self
+    }
+
+    /// Also post each entry on the `BroadcastChannel` named `name`, so that another tab
+    /// can subscribe to it and display the entries of all tabs. Each message is an object
+    /// `{ tab, level, target, message }`, where `tab` is a random id of the originating tab
+    /// and `message` is the entry as logged, without styles. If `BroadcastChannel` is not
+    /// available, the entries are only logged to the console.
+    pub fn broadcast_channel(mut self, name: &str) -> Self {
+        self.broadcast_channel = Some(name.to_string());
+        self
+    }
+
+    /// Also collect each entry, as logged but without styles, and `POST` the collected
+    /// entries to `url` as plain text, one entry per line, when the logger is flushed with
+    /// `log::logger().flush()` or `shutdown`.
+    pub fn remote_url(mut self, url: &str) -> Self {
+        self.remote_url = Some(url.to_string());
+        self
+    }
+
+    /// Also dispatch each entry as a `CustomEvent` named `event_name` on the first element
+    /// matching `target_selector`, or on `window` if none matches, e.g. for an in-app log
+    /// viewer which listens to them. The `detail` of the event is an object
+    /// `{ level, target, message }`, where `message` is the entry as logged, without styles.
+    /// The selector is resolved for each entry, so the element can be mounted after `init`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// wasm_logger::init(
+    ///     wasm_logger::Config::default().dispatch_dom_events("#log-panel", "app-log"),
+    /// );
+    /// ```
+    pub fn dispatch_dom_events(mut self, target_selector: &str, event_name: &str) -> Self {
+        self.dom_events = Some((target_selector.to_string(), event_name.to_string()));
+        self
+    }
+
+    /// Also add the entries of `min_level` or more severe as breadcrumbs of the error reporter
+    /// exposed as the global `global`, e.g. `Sentry`, by calling its
+    /// `Config::breadcrumb_method`, `addBreadcrumb` by default, with
+    /// `{ level, message, category }`. The `level` is the one of Sentry (`debug`, `info`,
+    /// `warning` or `error`), the `message` is the message of the record and the `category`
+    /// its target. The reporter then attaches the last entries to the errors which it captures.
+    ///
+    /// `global` can be a dotted path, e.g. `myApp.reporter`. It is resolved for each entry,
+    /// so the reporter can be loaded after `init`; the entries are skipped while it is absent.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// wasm_logger::init(
+    ///     wasm_logger::Config::default().breadcrumbs("Sentry", log::Level::Info),
+    /// );
+    /// ```
+    pub fn breadcrumbs(mut self, global: &str, min_level: Level) -> Self {
+        self.breadcrumbs = Some((global.to_string(), min_level));
+        self
+    }
+
+    /// Configure the method of the reporter of `Config::breadcrumbs` which is called for
+    /// each entry. Default to `addBreadcrumb`.
+    pub fn breadcrumb_method(mut self, method: &str) -> Self {
+        self.breadcrumb_method = method.to_string();
+        self
+    }
+
+    /// Also push each entry, as logged but without styles, to `capture`, e.g. to compare the
+    /// output of the app with a snapshot in a browser test. The `%c` directives are removed
+    /// and the styles dropped, which leaves the text a user reads.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let captured = Arc::new(Mutex::new(Vec::new()));
+    /// wasm_logger::init(wasm_logger::Config::default().capture_plain(captured.clone()));
+    /// log::info!("ready");
+    /// assert!(captured.lock().unwrap()[0].ends_with(" ready"));
+    /// ```
+    pub fn capture_plain(mut self, capture: Arc<Mutex<Vec<String>>>) -> Self {
+        self.plain_capture = Some(capture);
+        self
+    }
+
+    /// Also keep the last `max_lines` entries, as logged but without styles, in the
+    /// `localStorage` item `key`, as a JSON array of strings. They survive a reload or a
+    /// crash of the page, so that the next session can read them with `persisted_lines`,
+    /// e.g. to attach them to a bug report, then remove them with `clear_persisted`.
+    ///
+    /// The item is rewritten for each entry, which is slower than the other outputs. If the
+    /// storage is full or not available, e.g. in a private window, the entry is not kept.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// wasm_logger::init(
+    ///     wasm_logger::Config::default().persist_to_local_storage("app-logs", 200),
+    /// );
+    /// let previous_session = wasm_logger::persisted_lines();
+    /// if !previous_session.is_empty() {
+    ///     // attach `previous_session` to the crash report...
+    ///     wasm_logger::clear_persisted();
+    /// }
+    /// ```
+    pub fn persist_to_local_storage(mut self, key: &str, max_lines: usize) -> Self {
+        self.persisted = Some((key.to_string(), max_lines));
+        self
+    }
+
+    /// Keep the last `n` entries, as logged but without styles, in memory, so that `tail`
+    /// can return them, e.g. to attach the recent logs to a bug report sent from the app.
+    /// Unlike `Config::persist_to_local_storage`, they are cheap to keep but lost on reload.
+    /// Default to `0`, which keeps none.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// wasm_logger::init(wasm_logger::Config::default().keep_tail(100));
+    /// log::info!("opened the cart");
+    /// // when the user clicks on "Report a bug"
+    /// let recent_logs = wasm_logger::tail().join("\n");
+    /// wasm_logger::clear_tail();
+    /// ```
+    pub fn keep_tail(mut self, n: usize) -> Self {
+        self.tail_len = n;
+        self
+    }
+
+    /// Also log to the console the entries collected by `capture`. Default to `false`,
+    /// which only collects them.
+    pub fn echo_captured(mut self, yes: bool) -> Self {
+        self.echo_captured = yes;
+        self
+    }
+
+    /// Also stream the entries of `min_level` or more severe, without styles, to the
+    /// WebSocket server at `url`, one text frame per entry, e.g. for a live dashboard. The
+    /// entries are JSON if the layout is, e.g. `Config::json_lite`.
+    ///
+    /// The socket is opened at `init`. The entries logged while it connects are queued and
+    /// sent once it is open. If it closes, it is reopened after a delay which doubles from
+    /// 0.5s up to 30s, and the entries are queued meanwhile, up to the last
+    /// `Config::max_buffered_logs`. If the
+    /// environment has no `WebSocket`, or `url` is invalid, this is reported once with
+    /// `Config::internal_log_method` and the entries are not streamed.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// let config = wasm_logger::Config::default()
+    ///     .websocket_sink("ws://localhost:9000/logs", log::Level::Info);
+    /// wasm_logger::init(config);
+    /// ```
+    pub fn websocket_sink(mut self, url: &str, min_level: Level) -> Self {
+        self.websocket_sink = Some((url.to_string(), min_level));
+        self
+    }
+
+    /// Limit the number of entries held by each of the outputs which buffer them: the
+    /// entries of `Config::remote_url` until the next flush, and the queue of
+    /// `Config::websocket_sink` while it is disconnected. Once an output holds `max`
+    /// entries, the oldest one is dropped for each new one, and counted in the
+    /// `buffer_overflow` of `suppression_stats`. Default to 1000.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// let config = wasm_logger::Config::default()
+    ///     .remote_url("/logs")
+    ///     .max_buffered_logs(2);
+    /// wasm_logger::init(config);
+    /// log::info!("first"); // dropped by the third entry
+    /// log::info!("second");
+    /// log::info!("third");
+    /// assert_eq!(wasm_logger::suppression_stats().buffer_overflow, 1);
+    /// log::logger().flush(); // sends `second` and `third`
+    /// ```
+    pub fn max_buffered_logs(mut self, max: usize) -> Self {
+        self.max_buffered_logs = max;
+        self
+    }
+
+    /// Call `hook` with the number of entries of `Config::remote_url` and
+    /// `Config::websocket_sink` which are lost for good, e.g. to show an offline indicator
+    /// or to keep the entries in `localStorage` instead. It is called:
+    ///
+    /// - when entries are dropped from a buffer over `Config::max_buffered_logs`. The socket
+    ///   of `Config::websocket_sink` keeps reconnecting with a backoff, so its entries are
+    ///   only lost once the queue overflows, not on each failed attempt;
+    /// - when the `fetch` of a batch of `Config::remote_url` fails or gets an error status,
+    ///   with the size of the batch, since a batch is not sent again. A beacon of
+    ///   `Config::use_send_beacon` gives no feedback once accepted by the browser;
+    /// - at `shutdown`, with the entries still queued for `Config::websocket_sink`.
+    ///
+    /// The entries logged by the hook are [dropped](crate#nested-entries), so that they can
+    /// not fail again.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static LOST: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// let config = wasm_logger::Config::default()
+    ///     .remote_url("/logs")
+    ///     .on_delivery_failure(Box::new(|lost| {
+    ///         LOST.fetch_add(lost, Ordering::Relaxed);
+    ///     }));
+    /// wasm_logger::init(config);
+    /// ```
+    pub fn on_delivery_failure(mut self, hook: DeliveryFailureHook) -> Self {
+        self.on_delivery_failure = Some(Arc::new(hook));
+        self
+    }
+
+    /// Deliver the entries of `Config::remote_url` with `navigator.sendBeacon` rather than
+    /// `fetch`. The browser sends a beacon even if the page is unloading, so flushing in a
+    /// `beforeunload` or `pagehide` handler does not lose the last entries. A beacon is always
+    /// a `POST` without custom headers, and browsers limit its size, around 64 KiB. If the
+    /// beacon is refused or `sendBeacon` is not available, the entries are sent with `fetch`.
+    pub fn use_send_beacon(mut self, yes: bool) -> Self {
+        self.use_send_beacon = yes;
+        self
+    }
+
+    /// Also send the entries collected for `Config::remote_url` every `millis`
+    /// milliseconds, with `setInterval`, so that they reach the server during quiet
+    /// periods without waiting for a flush. Nothing is sent if no entry was collected
+    /// since the last time. `shutdown` clears the interval. By default, the entries are
+    /// only sent when the logger is flushed.
+    pub fn flush_interval_ms(mut self, millis: u32) -> Self {
+        self.flush_interval_ms = Some(millis);
+        self
+    }
+
+    /// Behave as in a Web Worker, which has no window, even if there is one, e.g. to try the
+    /// worker code paths in a page. The logger reaches `console`, `performance`, `crypto`,
+    /// `navigator` and `fetch` through the global object, so they work in workers too. The
+    /// features which need a window are disabled there: `Config::persist_to_local_storage`
+    /// keeps nothing, `Config::adapt_to_color_scheme` keeps the default colors,
+    /// `Config::use_send_beacon` falls back to `fetch`, the viewport of
+    /// `Config::log_environment_on_init` is unknown, and the events of
+    /// `Config::dispatch_dom_events` are dispatched on the global object. Default to
+    /// `false`, which detects whether there is a window.
+    pub fn assume_worker(mut self, yes: bool) -> Self {
+        self.assume_worker = yes;
+        self
+    }
+
+    /// Insert thousands separators into the large integers of messages, e.g. `1048576`
+    /// becomes `1,048,576`. Only standalone runs of at least 5 digits are grouped:
+    /// digits which are part of a word (`id12345`), of a decimal or time (`1.00000`,
+    /// `10:00:00`) or which start with a `0` are left untouched. The header, such as
+    /// the timestamp and the line number, is never affected. The separator is `,`
+    /// unless configured by `Config::thousands_separator`.
+    pub fn humanize_numbers(mut self, yes: bool) -> Self {
+        self.thousands_separator = match (yes, self.thousands_separator) {
+            (false, _) => None,
+            (true, separator) => Some(separator.unwrap_or(',')),
+        };
+        self
+    }
+
+    /// Configure the separator used by `Config::humanize_numbers`, e.g. `' '` for
+    /// `1 048 576`. This also enables `Config::humanize_numbers`.
+    pub fn thousands_separator(mut self, separator: char) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// If `true`, `log_bytes` counts in powers of 1000, in `kB`, `MB`, `GB`..., rather
+    /// than in powers of 1024, in `KiB`, `MiB`, `GiB`...
+    pub fn si_units(mut self, yes: bool) -> Self {
+        self.si_units = yes;
+        self
+    }
+
+    /// Also put a marker on the timeline of the devtools Performance panel for each entry,
+    /// with `console.timeStamp`, labeled with the level and the first line of the message
+    /// (at most 80 characters). This adds a console call per entry, so it is meant for
+    /// profiling sessions only.
+    pub fn performance_markers(mut self, yes: bool) -> Self {
+        self.performance_markers = yes;
+        self
+    }
+
+    /// Time the handling of each entry with `performance.now()`, and report once per target,
+    /// with the method of `Config::internal_log_method`, the targets whose entries took longer
+    /// than `micros` microseconds to render and deliver, e.g. because of a slow `Formatter`
+    /// or huge key-values. The precision of `performance.now()` is reduced by browsers,
+    /// often to 100µs or more.
+    pub fn warn_if_slow(mut self, micros: u32) -> Self {
+        self.slow_log_micros = Some(micros);
+        self
+    }
+
+    /// Log each entry as a collapsed `console.groupCollapsed` group: the rendered entry is
+    /// the header, and the metadata (target, module, location, timestamp and, with the `kv`
+    /// feature, the key-values) are its children. The header is not sent to the console
+    /// method of the level, so the devtools cannot filter these entries by level.
+    /// This adds several console calls per entry.
+    pub fn rich_grouping(mut self, yes: bool) -> Self {
+        self.rich_grouping = yes;
+        self
+    }
+
+    /// Put the consecutive entries of the same target in a `console.groupCollapsed` group
+    /// labeled with the target, so that bursts of entries of a module take a single row
+    /// until expanded. The group is closed by an entry of another target, by one second
+    /// without entries, or when the logger is flushed.
+    pub fn auto_group_by_target(mut self, yes: bool) -> Self {
+        self.auto_group_by_target = yes;
+        self
+    }
+
+    /// Hold the console output of the entries from `init` until `mark_ready` is called,
+    /// e.g. to hide the noise of the startup of a framework, then output them in order and
+    /// switch to live output. The held entries keep the timestamp of when they were
+    /// logged. They are also output if `mark_ready` is not called within
+    /// `Config::ready_timeout`, or at `shutdown`, so that they are never lost.
+    ///
+    /// Only the console is deferred: the other outputs, such as `Config::remote_url`,
+    /// receive the entries as they are logged.
+    pub fn defer_until_ready(mut self, yes: bool) -> Self {
+        self.defer_until_ready = yes;
+        self
+    }
+
+    /// The delay after `init` at which the entries held by `Config::defer_until_ready` are
+    /// output if `mark_ready` has not been called. Default to 10 seconds.
+    pub fn ready_timeout(mut self, millis: u32) -> Self {
+        self.ready_timeout_ms = millis;
+        self
+    }
+
+    /// Log an info entry at `init` which describes the environment: the version of
+    /// `wasm-logger`, `navigator.userAgent`, the size of the viewport and the settings of the
+    /// config, see `describe`. This anchors the entries collected by a remote output.
+    ///
+    /// The entry has the target `wasm_logger`, so the target rules such as
+    /// `Config::module_prefix` apply to it. The user agent or the viewport are `unknown`
+    /// where they are not available, e.g. in a worker.
+    pub fn log_environment_on_init(mut self, yes: bool) -> Self {
+        self.log_environment_on_init = yes;
+        self
+    }
+
+    /// Log an info entry at `init` with the hardware hints of the browser, e.g.
+    /// `hardware: 8 logical cores, 4 GB of device memory`, to relate performance issues to
+    /// low-end devices. The hints which the browser does not expose
+    /// (`navigator.hardwareConcurrency` and `navigator.deviceMemory`) are omitted, and
+    /// nothing is logged if there are none. The entry has the target `wasm_logger`.
+    pub fn log_hardware_info(mut self, yes: bool) -> Self {
+        self.log_hardware_info = yes;
+        self
+    }
+
+    /// Append the JavaScript stack trace, captured when the entry is logged, to the
+    /// message of error entries.
+    pub fn stack_trace_on_error(mut self, yes: bool) -> Self {
+        self.stack_trace_on_error = yes;
+        self
+    }
+
+    /// Remove the frames which contain any of `patterns` from the stack traces captured
+    /// by `Config::stack_trace_on_error`, e.g. `&["wasm-bindgen", "__wbg_"]` to hide the
+    /// frames of the generated glue code. The message itself is never affected.
+    pub fn trim_stack_frames(mut self, patterns: &[&str]) -> Self {
+        self.trim_stack_frames = patterns.iter().map(|p| p.to_string()).collect();
+        self
+    }
+
+    /// Configure the console method used by the diagnostics of `wasm-logger` itself,
+    /// such as an `init` failure or the number of entries dropped by
+    /// `Config::max_logs_per_second`. `None` silences them. Default to
+    /// `Some(ConsoleMethod::Warn)`, so that they are not reported by error monitoring tools.
+    pub fn internal_log_method(mut self, method: Option<ConsoleMethod>) -> Self {
+        self.internal_log_method = method;
+        self
+    }
+
+    /// Take the timestamp of a record from its `key` key-value entry, given either as
+    /// epoch milliseconds or as an RFC 3339 string. Records without such an entry, or
+    /// with a value which is neither, use the current time.
+    ///
+    /// Requires the `kv` feature.
+    #[cfg(feature = "kv")]
+    pub fn timestamp_from_kv(mut self, key: &str) -> Self {
+        self.timestamp_kv = Some(key.to_string());
+        self
+    }
+
+    /// Limit the nesting of the key-values shown by `Config::rich_grouping` to `depth`
+    /// levels of brackets: the content of deeper `{}`, `[]` and `()` is replaced with `…`,
+    /// e.g. `{a: {…}}` with a depth of 1. This keeps huge or recursive values readable.
+    /// Default to 4.
+    ///
+    /// Requires the `kv` feature.
+    #[cfg(feature = "kv")]
+    pub fn kv_max_depth(mut self, depth: usize) -> Self {
+        self.kv_max_depth = depth;
+        self
+    }
+
+    /// Truncate each key-value of a record rendered as text, by `Config::rich_grouping` or
+    /// `Config::logfmt`, to `max_len` characters followed by `…`, so that a single huge
+    /// value does not bloat the entry. The message and the typed values of
+    /// `Config::json_format` are not affected. By default, the values are not truncated.
+    ///
+    /// Requires the `kv` feature.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # #[cfg(feature = "kv")]
+    /// # {
+    /// use log::{Level, Record};
+    ///
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .logfmt()
+    ///     .timestamp_from_kv("time")
+    ///     .kv_value_max_len(8);
+    /// let body = "x".repeat(10_000);
+    /// let kvs: &[(&str, log::kv::Value)] = &[
+    ///     ("time", 0.into()),
+    ///     ("status", "ok".into()),
+    ///     ("body", body.as_str().into()),
+    /// ];
+    /// let record = Record::builder()
+    ///     .args(format_args!("sent"))
+    ///     .level(Level::Info)
+    ///     .target("api")
+    ///     .key_values(&kvs)
+    ///     .build();
+    /// assert_eq!(
+    ///     wasm_logger::render(&config, &record).text,
+    ///     concat!(
+    ///         "ts=1970-01-01T00:00:00.000Z level=info target=api msg=sent time=0 ",
+    ///         "status=ok body=xxxxxxxx…",
+    ///     ),
+    /// );
+    /// # }
+    /// ```
+    #[cfg(feature = "kv")]
+    pub fn kv_value_max_len(mut self, max_len: usize) -> Self {
+        self.kv_value_max_len = Some(max_len);
+        self
+    }
+
+    /// Apply the settings of `profile`, once, as a starting point to tweak further with the
+    /// other settings. Being a bundle of settings, it overrides the ones given before it,
+    /// so call it first. The profiles set:
+    ///
+    /// - `Profile::Development`: `Config::level` to trace, `Config::timestamp_format` to
+    ///   `TimestampFormat::Rfc3339`, `Config::adapt_to_color_scheme` and
+    ///   `Config::stack_trace_on_error`.
+    /// - `Profile::Production`: `Config::level` to warn, `Config::json_lite`, which is not
+    ///   styled, and `Config::dedup_consecutive`. Nothing leaves the browser until an output
+    ///   such as `Config::remote_url` is added.
+    /// - `Profile::Testing`: `Config::level` to trace, no timestamp, so that the entries do
+    ///   not depend on the clock, `Config::max_style_segments` to 0 for plain text, and
+    ///   `Config::keep_tail` of 1000 entries, to assert on with `tail`.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Config, Profile};
+    ///
+    /// let config = Config::default().profile(Profile::Testing).module_prefix("app");
+    /// let record = Record::builder()
+    ///     .level(Level::Trace)
+    ///     .file(Some("src/app.rs"))
+    ///     .line(Some(4))
+    ///     .args(format_args!("tick"))
+    ///     .build();
+    /// assert_eq!(wasm_logger::render(&config, &record).text, "TRACE src/app.rs:4 tick");
+    /// ```
+    pub fn profile(self, profile: Profile) -> Self {
+        match profile {
+            Profile::Development => self
+                .level(Level::Trace)
+                .timestamp_format(TimestampFormat::Rfc3339)
+                .adapt_to_color_scheme(true)
+                .stack_trace_on_error(true),
+            Profile::Production => self.level(Level::Warn).json_lite().dedup_consecutive(true),
+            Profile::Testing => {
+                let mut config = self
+                    .level(Level::Trace)
+                    .max_style_segments(0)
+                    .keep_tail(1000);
+                config.timestamp_format = None;
+                config.timestamp_items = None;
+                config
+            }
+        }
+    }
+
+    /// Mimic the default format of `env_logger`:
+    /// `[2024-01-01T00:00:00Z INFO  my_app::module] message`.
+    ///
+    /// This sets the timestamp format to RFC 3339 in UTC with a precision of seconds,
+    /// and shows the target of the record instead of its file and line. It differs
+    /// from `env_logger` in that the level is colored with CSS, as configured by
+    /// `Config::style_mode`, rather than with ANSI escape codes.
+    pub fn env_logger_style(mut self) -> Self {
+        self = self.timestamp_format(TimestampFormat::Custom("%Y-%m-%dT%H:%M:%SZ".to_string()));
+        self.output_format = OutputFormat::EnvLogger;
+        self
+    }
+
+    /// Log each entry as a single-line JSON object with the fields `v`, see
+    /// `Config::schema_version`, `timestamp` (RFC 3339), `level`, `target`, `file`, `line`,
+    /// `message` and `fields`, instead of the human-readable layout. Styling does not apply
+    /// to JSON entries.
+    ///
+    /// `fields` holds the `Config::global_field`s and, with the `kv` feature, the
+    /// key-values of the record, which keep their type: integers, floats and booleans are
+    /// JSON numbers and booleans, and an empty value is `null`. Other values, such as the
+    /// ones captured with `Debug`, are strings. A key-value replaces the global field of
+    /// the same key.
+    ///
+    /// Requires the `json` feature.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # #[cfg(all(feature = "json", feature = "kv"))]
+    /// # {
+    /// use log::{Level, Record};
+    ///
+    /// // A timestamp from a key-value, to render without a browser
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .json_format()
+    ///     .timestamp_from_kv("ts");
+    /// let kvs: &[(&str, log::kv::Value)] = &[
+    ///     ("ts", 0.into()),
+    ///     ("count", 5.into()),
+    ///     ("cached", true.into()),
+    ///     ("user", "ferris".into()),
+    /// ];
+    /// let record = Record::builder()
+    ///     .args(format_args!("loaded"))
+    ///     .level(Level::Info)
+    ///     .key_values(&kvs)
+    ///     .build();
+    /// let text = wasm_logger::render(&config, &record).text;
+    /// assert!(text.contains(r#""fields":{"cached":true,"count":5,"ts":0,"user":"ferris"}"#));
+    /// assert!(!text.contains(r#""count":"5""#));
+    /// assert!(text.contains(r#""v":1"#));
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn json_format(mut self) -> Self {
+        self.output_format = OutputFormat::Json;
+        self
+    }
+
+    /// Log each entry as a compact single-line JSON object, without depending on
+    /// `serde_json`: `{"v":1,"ts":1700000000000,"lvl":"INFO","target":"my_app","msg":"message"}`,
+    /// where `v` is the `Config::schema_version` and `ts` is in milliseconds since the Unix
+    /// epoch.
+    ///
+    /// Unlike `Config::json_format`, it has no `file` and `line`, and the key-values of the
+    /// records are left out: `fields` is only added for the `Config::global_field`s, if
+    /// any. Styling does not apply to JSON entries.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// wasm_logger::init(wasm_logger::Config::default().json_lite());
+    /// ```
+    pub fn json_lite(mut self) -> Self {
+        self.output_format = OutputFormat::JsonLite;
+        self
+    }
+
+    /// Log each entry as space-separated `key=value` pairs, in the logfmt format:
+    /// `ts=2024-01-01T00:00:00.000Z level=info target=my_app msg="cache warmed"`, followed
+    /// by the `Config::global_field`s and, with the `kv` feature, the key-values of the
+    /// record. A value is quoted if it is empty or contains spaces, control characters,
+    /// `=` or `"`, and the `"` and `\` in a quoted value are escaped. The characters which
+    /// are not allowed in a key are replaced with `_`. Styling does not apply.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # #[cfg(feature = "kv")]
+    /// # {
+    /// use log::{Level, Record};
+    ///
+    /// // A timestamp from a key-value, to render without a browser
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .logfmt()
+    ///     .timestamp_from_kv("time")
+    ///     .global_field("app", "shop front");
+    /// let kvs: &[(&str, log::kv::Value)] = &[
+    ///     ("time", 0.into()),
+    ///     ("empty", "".into()),
+    ///     ("query", "a=b".into()),
+    /// ];
+    /// let record = Record::builder()
+    ///     .args(format_args!(r#"said "hi""#))
+    ///     .level(Level::Warn)
+    ///     .target("shop")
+    ///     .key_values(&kvs)
+    ///     .build();
+    /// assert_eq!(
+    ///     wasm_logger::render(&config, &record).text,
+    ///     concat!(
+    ///         r#"ts=1970-01-01T00:00:00.000Z level=warn target=shop msg="said \"hi\"" "#,
+    ///         r#"app="shop front" time=0 empty="" query="a=b""#,
+    ///     ),
+    /// );
+    /// # }
+    /// ```
+    pub fn logfmt(mut self) -> Self {
+        self.output_format = OutputFormat::Logfmt;
+        self
+    }
+
+    /// Set the `v` field of the JSON layouts, `Config::json_format` and
+    /// `Config::json_lite`, so that the consumers of the entries, e.g. the server of
+    /// `Config::remote_url`, know how to parse them. The human-readable layouts are not
+    /// affected. Default to `SCHEMA_VERSION`, the version of the fields of this crate: set
+    /// it to keep a version of your own, e.g. along with `Config::global_field`s.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// # #[cfg(feature = "kv")]
+    /// # {
+    /// use log::{Level, Record};
+    ///
+    /// // A timestamp from a key-value, to render without a browser
+    /// let config = wasm_logger::Config::new(Level::Info)
+    ///     .json_lite()
+    ///     .schema_version(7)
+    ///     .timestamp_from_kv("ts");
+    /// let kvs: &[(&str, log::kv::Value)] = &[("ts", 0.into())];
+    /// let record = Record::builder()
+    ///     .args(format_args!("ready"))
+    ///     .level(Level::Info)
+    ///     .key_values(&kvs)
+    ///     .build();
+    /// assert!(wasm_logger::render(&config, &record).text.starts_with(r#"{"v":7,"ts":0,"#));
+    /// # }
+    /// ```
+    pub fn schema_version(mut self, version: u32) -> Self {
+        self.schema_version = version;
+        self
+    }
+
+    /// Render the entries with `formatter` instead of the built-in layouts. The logger
+    /// still picks the console method of the level and delivers the entries to the
+    /// outputs. `FormatContext::render_default` gives the built-in rendering of a record,
+    /// e.g. to decorate it.
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::{Config, FormatContext, Formatter, RenderedLog};
+    ///
+    /// struct Bracketed;
+    ///
+    /// impl Formatter for Bracketed {
+    ///     fn format(&self, record: &Record<'_>, ctx: &FormatContext<'_>) -> RenderedLog {
+    ///         RenderedLog {
+    ///             text: format!("%c[{}]%c {}", record.level(), record.args()),
+    ///             styles: vec![ctx.level_style(record.level()).to_string(), String::new()],
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let config = Config::new(Level::Info).formatter(Box::new(Bracketed));
+    /// let record = Record::builder().args(format_args!("ready")).level(Level::Info).build();
+    /// assert_eq!(wasm_logger::render(&config, &record).plain_text(), "[INFO] ready");
+    /// ```
+    pub fn formatter(mut self, formatter: Box<dyn Formatter>) -> Self {
+        self.formatter = Some(formatter);
+        self
+    }
+
+    /// Also deliver the entries of `levels` to `sink`, e.g. to send only the warnings and
+    /// errors to a server while the console gets every entry. The sink receives the
+    /// entries which pass the filters of the logger, rendered, after `Config::pre_emit`.
+    /// Calling it multiple times adds more sinks.
+    ///
+    /// `levels` is an explicit set, so it can leave out any level, e.g. only `Level::Warn`
+    /// and `Level::Info` to audit them while the errors are reported elsewhere. A minimum
+    /// level, as taken by the built-in outputs such as `Config::websocket_sink`, is the set
+    /// of the levels up to it: `&[Level::Error, Level::Warn]` for warnings and above.
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use log::Level;
+    /// use wasm_logger::{Config, RenderedLog, Sink};
+    ///
+    /// struct Alert;
+    ///
+    /// impl Sink for Alert {
+    ///     fn write(&self, level: Level, target: &str, rendered: &RenderedLog) {
+    ///         let text = format!("{} in {}: {}", level, target, rendered.plain_text());
+    ///         web_sys::window().unwrap().alert_with_message(&text).unwrap();
+    ///     }
+    /// }
+    ///
+    /// wasm_logger::init(Config::new(Level::Debug).add_sink(Box::new(Alert), &[Level::Error]));
+    /// ```
+    pub fn add_sink(mut self, sink: Box<dyn Sink>, levels: &[Level]) -> Self {
+        let bits = levels
+            .iter()
+            .fold(0, |bits, level| bits | level_bit(*level));
+        self.sinks.push((sink, bits));
+        self
+    }
+
+    /// Also call `hook` with the data of each logged record, its level, target, location,
+    /// timestamp, message and key-values, e.g. to count the errors per target or show the
+    /// entries in a panel of the app, without parsing the rendered text. It is called for
+    /// the records which pass the filters and are not dropped by `Config::pre_emit`, after
+    /// the console and the sinks. The entries logged by the hook are
+    /// [dropped](crate#nested-entries).
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use std::sync::atomic::{AtomicU32, Ordering};
+    ///
+    /// use log::Level;
+    ///
+    /// static ERRORS: AtomicU32 = AtomicU32::new(0);
+    ///
+    /// wasm_logger::init(wasm_logger::Config::default().on_record(Box::new(|data| {
+    ///     if data.level == Level::Error {
+    ///         ERRORS.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// })));
+    /// ```
+    pub fn on_record(mut self, hook: RecordHook) -> Self {
+        self.on_record = Some(hook);
+        self
+    }
+
+    /// Call `hook` with each rendered entry right before it is logged, to log it as is,
+    /// replace it or drop it, see `EmitDecision`. The decision applies to the console call
+    /// and to the outputs which follow it, such as `Config::broadcast_channel` and
+    /// `Config::capture_plain`.
+    ///
+    /// The hook runs for every entry which passed the filters, after it has been rendered, so
+    /// a slow hook slows down all logging: prefer the filters, e.g. `Config::module_level`,
+    /// to drop entries by their level or target. The entries logged by the hook are
+    /// [dropped](crate#nested-entries).
+    ///
+    /// ## Examples
+    /// ```rust,no_run
+    /// use wasm_logger::{Config, EmitDecision};
+    ///
+    /// wasm_logger::init(Config::default().pre_emit(Box::new(|_level, rendered| {
+    ///     if rendered.text.contains("password") {
+    ///         EmitDecision::Drop
+    ///     } else {
+    ///         EmitDecision::Emit
+    ///     }
+    /// })));
+    /// ```
+    pub fn pre_emit(mut self, hook: PreEmitHook) -> Self {
+        self.pre_emit = Some(hook);
+        self
+    }
+
+    /// Call `hook` with each record before it is rendered, to change the layout of this
+    /// entry only through an `EffectiveConfig`, e.g. to make the errors stand out during an
+    /// error storm, without reconfiguring the logger.
+    ///
+    /// The hook runs for every entry which passed the filters, so it can not let through
+    /// an entry which they dropped, and a slow hook slows down all logging. The entries
+    /// logged by the hook are [dropped](crate#nested-entries).
+    ///
+    /// ## Examples
+    /// ```rust
+    /// use log::{Level, Record};
+    /// use wasm_logger::Config;
+    ///
+    /// let config = Config::default().dynamic(Box::new(|record, effective| {
+    ///     if record.level() == Level::Error {
+    ///         effective.show_location = false;
+    ///         effective.message_css = Some("font-weight: bold".to_string());
+    ///     }
+    /// }));
+    /// let record = |level| {
+    ///     let record = Record::builder()
+    ///         .level(level)
+    ///         .file(Some("src/main.rs"))
+    ///         .line(Some(7))
+    ///         .args(format_args!("disk full"))
+    ///         .build();
+    ///     wasm_logger::render(&config, &record)
+    /// };
+    /// assert_eq!(record(Level::Warn).plain_text(), "WARN src/main.rs:7 disk full");
+    /// let error = record(Level::Error);
+    /// assert_eq!(error.plain_text(), "ERROR  disk full");
+    /// assert_eq!(error.styles.last().unwrap(), "font-weight: bold");
+    /// ```
+    pub fn dynamic(mut self, hook: DynamicHook) -> Self {
+        self.dynamic = Some(hook);
+        self
+    }
+}
+
+/// A malformed directive given to `Config::parse_directives`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveError {
+    directive: String,
+}
+
+impl DirectiveError {
+    /// The malformed directive
+    pub fn directive(&self) -> &str {
+        &self.directive
+    }
+}
+
+impl std::fmt::Display for DirectiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid log directive: `{}`", self.directive)
+    }
+}
+
+impl std::error::Error for DirectiveError {}
+
+/// The log styles
+#[derive(Clone)]
+struct Style {
+    lvl_trace: String,
+    lvl_debug: String,
+    lvl_info: String,
+    lvl_warn: String,
+    lvl_error: String,
+    tgt: String,
+    args: String,
+}
+
+/// The theme which the level colors are picked for
+#[derive(Clone, Copy)]
+enum ColorScheme {
+    /// The original colors, regardless of the theme
+    Any,
+    Light,
+    Dark,
+}
+
+impl Style {
+    fn new(config: &Config, scheme: ColorScheme) -> Style {
+        let [trace, debug, info, warn, error] = match config.severity_gradient {
+            Some(shift) => severity_gradient(shift, scheme),
+            None => match scheme {
+                ColorScheme::Any => ["gray", "blue", "green", "orange", "darkred"],
+                ColorScheme::Light => ["#5f6368", "#1a56c4", "#137333", "#b05a00", "#a50e0e"],
+                ColorScheme::Dark => ["#9aa0a6", "#4a8cf7", "#2e9e4f", "#d48a00", "#e0453a"],
+            }
+            .map(String::from),
+        };
+        let level_style = |color: &str| {
+            let color = if config.badge_opacity < 1.0 {
+                format!(
+                    "color-mix(in srgb, {} {}%, transparent)",
+                    color,
+                    (config.badge_opacity * 100.0).round()
+                )
+            } else {
+                color.to_string()
+            };
+            match config.style_mode {
+                StyleMode::Badge => format!("color: white; padding: 0 3px; background: {};", color),
+                StyleMode::Text => format!("color: {}; padding: 0 3px; font-weight: bold;", color),
+            }
+        };
+        let color = |level: Level| match config.severity(level).map_or(level, Severity::color_level)
+        {
+            Level::Trace => trace.as_str(),
+            Level::Debug => debug.as_str(),
+            Level::Info => info.as_str(),
+            Level::Warn => warn.as_str(),
+            Level::Error => error.as_str(),
+        };
+        Style {
+            lvl_trace: level_style(color(Level::Trace)),
+            lvl_debug: level_style(color(Level::Debug)),
+            lvl_info: level_style(color(Level::Info)),
+            lvl_warn: level_style(color(Level::Warn)),
+            lvl_error: level_style(color(Level::Error)),
+            tgt: String::from("font-weight: bold; color: inherit"),
+            args: String::from("background: inherit; color: inherit"),
+        }
+    }
+
+    /// This style, with `css` for the message
+    fn with_message_style(&self, css: &str) -> Style {
+        Style {
+            args: css.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// The style of the level badge of `level`
+    fn level(&self, level: Level) -> &str {
+        match level {
+            Level::Trace => &self.lvl_trace,
+            Level::Debug => &self.lvl_debug,
+            Level::Info => &self.lvl_info,
+            Level::Warn => &self.lvl_warn,
+            Level::Error => &self.lvl_error,
+        }
+    }
+}
+
+/// The colors of `Config::severity_gradient`, from trace to error
+fn severity_gradient(base_hue_shift: f32, scheme: ColorScheme) -> [String; 5] {
+    let lighten = match scheme {
+        ColorScheme::Dark => 20.0,
+        ColorScheme::Any | ColorScheme::Light => 0.0,
+    };
+    let color = |index: u8| {
+        // The severity, from 0 for trace to 1 for error
+        let severity = f32::from(index) / 4.0;
+        // Bent so that warn is orange rather than yellow
+        let hue = 240.0 * (1.0 - severity).powf(1.2) + base_hue_shift;
+        format!(
+            "hsl({}, {}%, {}%)",
+            hue.round().rem_euclid(360.0),
+            (30.0 + 50.0 * severity).round(),
+            (50.0 - 15.0 * severity + lighten).round()
+        )
+    };
+    [color(0), color(1), color(2), color(3), color(4)]
+}
+
+/// A token bucket which holds up to `capacity` tokens and refills at `capacity` tokens per second
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    /// The `Date::now()` of the last refill, in milliseconds
+    last_refill: f64,
+    /// The number of entries dropped since the bucket was last able to pay for one
+    dropped: u64,
+}
+
+impl TokenBucket {
+    fn new(per_second: u32, now: f64) -> Self {
+        Self {
+            capacity: per_second as f64,
+            tokens: per_second as f64,
+            last_refill: now,
+            dropped: 0,
+        }
+    }
+
+    /// Try to take a token. On success, return the number of entries dropped before this one.
+    fn take(&mut self, now: f64) -> Option<u64> {
+        let elapsed = (now - self.last_refill).max(0.0);
+        self.tokens = (self.tokens + elapsed * self.capacity / 1000.0).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Some(std::mem::take(&mut self.dropped))
+        } else {
+            self.dropped += 1;
+            None
+        }
+    }
+}
+
+/// The `Config::module_level`s by prefix, so that the level of a target is found with a
+/// lookup per `::` of the target rather than by matching every rule, and the levels found
+/// for the targets already seen
+struct ModuleLevels {
+    rules: HashMap<String, LevelFilter>,
+    /// Most records come from a few targets: the first `MODULE_LEVEL_CACHE_LEN` targets
+    /// seen are kept with their level, so that their next lookups are a single one
+    cache: RwLock<HashMap<String, Option<LevelFilter>>>,
+}
+
+/// The maximum number of targets kept by `ModuleLevels`, in case they are generated
+const MODULE_LEVEL_CACHE_LEN: usize = 1024;
+
+impl ModuleLevels {
+    /// Index `rules`, the last rule of a prefix winning
+    fn new(rules: &[(String, LevelFilter)]) -> Self {
+        Self {
+            rules: rules.iter().cloned().collect(),
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The level of the longest prefix which matches `target` as `matches_module_prefix`
+    /// does: `target` itself, then each of its parents, with or without the trailing `::`,
+    /// then the empty prefix
+    fn get(&self, target: &str) -> Option<LevelFilter> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        if let Some(level) = self
+            .cache
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(target)
+        {
+            return *level;
+        }
+        let level = std::iter::once(target)
+            .chain(
+                target
+                    .rmatch_indices("::")
+                    .flat_map(|(i, _)| [&target[..i + 2], &target[..i]]),
+            )
+            .chain(std::iter::once(""))
+            .find_map(|prefix| self.rules.get(prefix).copied());
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        if cache.len() < MODULE_LEVEL_CACHE_LEN {
+            cache.insert(target.to_string(), level);
+        }
+        level
+    }
+}
+
+/// The logger of `init` or of a config of `register_config`
+struct WasmLogger {
+    config: Config,
+    /// Behind a `RwLock` because `Config::adapt_to_color_scheme` replaces it when the
+    /// theme changes
+    style: RwLock<Style>,
+    /// Enforces `Config::max_logs_per_second`. Behind a `Mutex` because `Log::log` only
+    /// has `&self`.
+    rate_limit: Option<Mutex<TokenBucket>>,
+    /// The `Config::module_level`s, indexed for `WasmLogger::module_level`
+    module_levels: ModuleLevels,
+    /// The timestamp of the last entry emitted, see `Config::elide_repeated_timestamps`
+    last_timestamp: Mutex<Option<String>>,
+}
+
+impl Log for WasmLogger {
+    /// A record is rejected if the logger has been shut down, if its target is muted by
+    /// `mute_target`, or if `focus` is active and its target is not focused. The records of
+    /// a `Config::always_on_target` are then let through, unless their module level is off.
+    /// The checks of the other records are ordered from the cheapest to the most expensive,
+    /// and stop at the first one which rejects the record:
+    /// 1. the level has not been turned off by `set_level_enabled`,
+    /// 2. the level is within the maximum level, which `boost_level` may have raised,
+    /// 3. the level is within the level of its target, see `WasmLogger::module_level_enabled`,
+    /// 4. the target passes the target rules, see `WasmLogger::target_enabled`, unless it
+    ///    is focused.
+    ///
+    /// A record rejected by its level thus never runs the target matching.
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let unmarked = split_target_style(metadata.target());
+        let target = unmarked
+            .as_ref()
+            .map_or(metadata.target(), |(target, _)| target.as_str());
+        if SHUT_DOWN.load(Ordering::Relaxed) || muted(target) {
+            return false;
+        }
+        let focused = focused(target);
+        if focused == Some(false) {
+            return false;
+        }
+        if self.always_on(target) {
+            return self.module_level(target) != Some(LevelFilter::Off);
+        }
+        ENABLED_LEVELS.load(Ordering::Relaxed) & level_bit(metadata.level()) != 0
+            && metadata.level() <= log::max_level()
+            && self.module_level_enabled(metadata)
+            && (focused == Some(true) || self.target_enabled(target))
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        match split_target_style(record.target()) {
+            Some((target, message_css)) => {
+                self.log_record(&with_target(record, &target), message_css)
+            }
+            None => self.log_record(record, None),
+        }
+    }
+
+    /// Close the group of `Config::auto_group_by_target`, report the repeats of
+    /// `Config::dedup_consecutive` and send the entries collected for `Config::remote_url`,
+    /// if any
+    fn flush(&self) {
+        close_auto_group();
+        // The next entry is not a repeat, even if it is the same
+        if let Some((_, _, _, repeats)) = LAST_ENTRY.with(|last| last.borrow_mut().take()) {
+            self.report_repeats(repeats);
+        }
+        self.send_remote();
+    }
+}
+
+impl WasmLogger {
+    fn new(config: Config) -> Self {
+        let rate_limit = config
+            .max_logs_per_second
+            .map(|max| Mutex::new(TokenBucket::new(max, js_sys::Date::now())));
+        WasmLogger {
+            style: RwLock::new(Style::new(&config, ColorScheme::Any)),
+            module_levels: ModuleLevels::new(&config.module_levels),
+            config,
+            rate_limit,
+            last_timestamp: Mutex::new(None),
+        }
+    }
+
+    /// Call the `Config::on_delivery_failure` hook, if any, with `dropped` entries, unless
+    /// there are none
+    fn report_delivery_failure(&self, dropped: usize) {
+        if let (Some(hook), true) = (&self.config.on_delivery_failure, dropped > 0) {
+            report_delivery_failure(hook, dropped);
+        }
+    }
+
+    /// Send the entries collected for `Config::remote_url`, if any
+    fn send_remote(&self) {
+        let url = match self.config.remote_url {
+            Some(ref url) => url,
+            None => return,
+        };
+        let entries = REMOTE_BUFFER.with(|buffer| std::mem::take(&mut *buffer.borrow_mut()));
+        if entries.is_empty() {
+            return;
+        }
+        let count = entries.len();
+        let body = JsValue::from_str(&Vec::from(entries).join("\n"));
+        let url = JsValue::from_str(url);
+        // `navigator.sendBeacon` is only defined in windows
+        if self.config.use_send_beacon && window().is_some() {
+            let sent = global_method(navigator().as_ref(), "sendBeacon").and_then(
+                |(navigator, send_beacon)| send_beacon.call2(&navigator, &url, &body).ok(),
+            );
+            if sent.and_then(|sent| sent.as_bool()) == Some(true) {
+                return;
+            }
+        }
+        let init = web_sys::RequestInit::new();
+        init.set_method("POST");
+        init.set_body(&body);
+        // The `fetch` of the global object, which workers have too
+        let sent = global_method(None, "fetch")
+            .and_then(|(global, fetch)| fetch.call2(&global, &url, &init).ok());
+        if let Some(ref hook) = self.config.on_delivery_failure {
+            match sent.map(js_sys::Promise::from) {
+                Some(response) => {
+                    let hook = Arc::clone(hook);
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let ok = wasm_bindgen_futures::JsFuture::from(response)
+                            .await
+                            .ok()
+                            .and_then(|response| {
+                                js_sys::Reflect::get(&response, &JsValue::from_str("ok")).ok()
+                            })
+                            .and_then(|ok| ok.as_bool());
+                        if ok != Some(true) {
+                            report_delivery_failure(&hook, count);
+                        }
+                    });
+                }
+                None => report_delivery_failure(hook, count),
+            }
+        }
+    }
+
+    /// Log `record`, whose target has no style marker, with the message style `message_css`
+    /// if any, see `Log::log`
+    fn log_record(&self, record: &Record<'_>, message_css: Option<&str>) {
+        // Taken first, so that it only applies to the entry logged by `log_as`
+        let method = METHOD_OVERRIDE.with(Cell::take);
+        if !self.enabled(record.metadata()) || self.config.skips(record) {
+            SUPPRESSED_FILTERED.fetch_add(1, Ordering::Relaxed);
+        } else {
+            // An entry logged while another one is handled would be interleaved with it,
+            // and would deadlock on the rate limit, so it is dropped.
+            if IN_LOG.with(|in_log| in_log.replace(true)) {
+                if !NESTED_REPORTED.with(|reported| reported.replace(true)) {
+                    internal_log(
+                        self.config.internal_log_method,
+                        &format!(
+                            "wasm-logger: dropped an entry of `{}` logged while another entry \
+                             was being logged, further ones are dropped silently",
+                            record.target()
+                        ),
+                    );
+                }
+                return;
+            }
+            let _guard = InLogGuard;
+            if self.config.dedup_consecutive || self.config.dedup_normalized {
+                let mut message = record.args().to_string();
+                if self.config.dedup_normalized {
+                    message = normalize_message(&message);
+                }
+                if self.is_repeat(record.level(), record.target(), message) {
+                    SUPPRESSED_DEDUPED.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+            if let Some(ref bucket) = self.rate_limit {
+                let mut bucket = bucket.lock().unwrap_or_else(|e| e.into_inner());
+                match bucket.take(js_sys::Date::now()) {
+                    Some(0) => {}
+                    Some(dropped) => internal_log(
+                        self.config.internal_log_method,
+                        &format!("[{dropped} logs dropped]"),
+                    ),
+                    None => {
+                        SUPPRESSED_RATE_LIMITED.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                }
+            }
+            let start = self.config.slow_log_micros.and_then(|_| performance_now());
+            let (rendered, timestamp) = self.render(record, message_css);
+            let rendered = match self.config.apply_pre_emit(record.level(), rendered) {
+                Some(rendered) => rendered,
+                None => return,
+            };
+            if let Some(max) = self.config.max_total_logs {
+                let logged = TOTAL_LOGGED.fetch_add(1, Ordering::Relaxed);
+                if logged >= max as u64 {
+                    if logged == max as u64 {
+                        internal_log(
+                            self.config.internal_log_method,
+                            "wasm-logger: output limit reached, logging paused",
+                        );
+                    }
+                    SUPPRESSED_OVER_LIMIT.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+            }
+            self.emitted(timestamp);
+            let group = if self.config.auto_group_by_target {
+                Some(self.config.display_target(record))
+            } else {
+                None
+            };
+            let metadata = if self.config.rich_grouping {
+                self.metadata_lines(record)
+            } else {
+                Vec::new()
+            };
+            let captured = CAPTURES.with(|captures| {
+                let mut captures = captures.borrow_mut();
+                if captures.is_empty() {
+                    return false;
+                }
+                let text = rendered.plain_text();
+                for lines in captures.iter_mut() {
+                    lines.push(text.clone());
+                }
+                true
+            });
+            if !captured || self.config.echo_captured {
+                let deferred = DEFERRED.with(|deferred| match deferred.borrow_mut().as_mut() {
+                    Some(entries) => {
+                        entries.push(DeferredEntry {
+                            level: record.level(),
+                            method,
+                            group: group.map(String::from),
+                            metadata: metadata.clone(),
+                            rendered: rendered.clone(),
+                        });
+                        true
+                    }
+                    None => false,
+                });
+                if !deferred {
+                    self.write_console(record.level(), method, group, &metadata, &rendered);
+                }
+            }
+            self.broadcast(record, &rendered);
+            self.dispatch_dom_event(record, &rendered);
+            self.add_breadcrumb(record);
+            if self.config.remote_url.is_some() {
+                let entry = self.config.remote_entry(&rendered);
+                let dropped = REMOTE_BUFFER.with(|buffer| {
+                    push_bounded(
+                        &mut buffer.borrow_mut(),
+                        entry,
+                        self.config.max_buffered_logs,
+                    )
+                });
+                self.report_delivery_failure(dropped);
+            }
+            match self.config.websocket_sink {
+                Some((_, min_level)) if record.level() <= min_level => {
+                    let dropped = send_websocket(
+                        self.config.remote_entry(&rendered),
+                        self.config.max_buffered_logs,
+                    );
+                    self.report_delivery_failure(dropped);
+                }
+                _ => {}
+            }
+            for (sink, levels) in &self.config.sinks {
+                if levels & level_bit(record.level()) != 0 {
+                    sink.write(
+                        record.level(),
+                        self.config.display_target(record),
+                        &rendered,
+                    );
+                }
+            }
+            if let Some(ref hook) = self.config.on_record {
+                hook(self.record_data(record));
+            }
+            if let Some(ref capture) = self.config.plain_capture {
+                capture
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .push(rendered.plain_text());
+            }
+            if let Some((ref key, max_lines)) = self.config.persisted {
+                persist_line(key, max_lines, rendered.plain_text());
+            }
+            if self.config.tail_len > 0 {
+                let mut tail = TAIL.lock().unwrap_or_else(|e| e.into_inner());
+                if tail.len() >= self.config.tail_len {
+                    tail.pop_front();
+                }
+                tail.push_back(rendered.plain_text());
+            }
+            if self.config.performance_markers {
+                let message = record.args().to_string();
+                let first_line = message.lines().next().unwrap_or_default();
+                let label: String = format!("{} {}", record.level(), first_line)
+                    .chars()
+                    .take(80)
+                    .collect();
+                console::time_stamp_with_data(&JsValue::from_str(&label));
+            }
+            if let (Some(budget), Some(start)) = (self.config.slow_log_micros, start) {
+                self.check_duration(record.target(), start, budget);
+            }
+        }
+    }
+
+    /// Whether the entry is a repeat of the previous one, see `Config::dedup_consecutive`.
+    /// If it is not, the repeats of the previous one are reported, and it becomes the
+    /// previous one.
+    fn is_repeat(&self, level: Level, target: &str, message: String) -> bool {
+        let previous = LAST_ENTRY.with(|last| {
+            let mut last = last.borrow_mut();
+            if let Some((last_level, last_target, last_message, repeats)) = last.as_mut() {
+                if *last_level == level && last_target == target && *last_message == message {
+                    *repeats += 1;
+                    return None;
+                }
+            }
+            Some(last.replace((level, target.to_string(), message, 0)))
+        });
+        match previous {
+            None => true,
+            Some(previous) => {
+                if let Some((_, _, _, repeats)) = previous {
+                    self.report_repeats(repeats);
+                }
+                false
+            }
+        }
+    }
+
+    /// Report the `repeats` of an entry collapsed by `Config::dedup_consecutive`, if any
+    fn report_repeats(&self, repeats: u64) {
+        if repeats > 0 {
+            internal_log(
+                self.config.internal_log_method,
+                &format!("[previous entry repeated {repeats} times]"),
+            );
+        }
+    }
+
+    /// Output `rendered` to the console with `method`, or else the method of `level`, in the
+    /// group of `group` if any, and as a group of the `metadata` lines with
+    /// `Config::rich_grouping`
+    fn write_console(
+        &self,
+        level: Level,
+        method: Option<ConsoleMethod>,
+        group: Option<&str>,
+        metadata: &[String],
+        rendered: &RenderedLog,
+    ) {
+        if let Some(target) = group {
+            open_auto_group(target);
+        }
+        if self.config.rich_grouping {
+            console::group_collapsed(&rendered.console_args());
+            for line in metadata {
+                console::log_1(&JsValue::from_str(line));
+            }
+            console::group_end();
+        } else if let Some(method) = method {
+            method.call(&rendered.console_args());
+        } else {
+            self.console_log(level, &rendered.console_args());
+        }
+    }
+
+    /// The level for `log::set_max_level`: the most verbose of `Config::new` and the
+    /// `Config::module_level`s which is on in `levels`, or trace if there is a
+    /// `Config::always_on_target`
+    fn max_level(&self, levels: u8) -> LevelFilter {
+        if !self.config.always_on_targets.is_empty() {
+            return LevelFilter::Trace;
+        }
+        let max_level = self
+            .config
+            .module_levels
+            .iter()
+            .map(|(_, level)| *level)
+            .chain(
+                self.config
+                    .level_schedule
+                    .iter()
+                    .map(|(_, _, level)| level.to_level_filter()),
+            )
+            .fold(self.config.level.to_level_filter(), Ord::max);
+        #[cfg(feature = "regex")]
+        let max_level = self
+            .config
+            .target_regexes
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(max_level, Ord::max);
+        enabled_max_level(max_level, levels)
+    }
+
+    /// Whether `target` matches a `Config::always_on_target`
+    fn always_on(&self, target: &str) -> bool {
+        self.config
+            .always_on_targets
+            .iter()
+            .any(|prefix| matches_module_prefix(target, prefix))
+    }
+
+    /// The level of the first `Config::target_regex` which matches `target`, or else the
+    /// most specific `Config::module_level` of `target`, if any
+    fn module_level(&self, target: &str) -> Option<LevelFilter> {
+        #[cfg(feature = "regex")]
+        if let Some((_, level)) = self
+            .config
+            .target_regexes
+            .iter()
+            .find(|(regex, _)| regex.is_match(target))
+        {
+            return Some(*level);
+        }
+        self.module_levels.get(target)
+    }
+
+    /// Whether the level of `metadata` is within the most specific `Config::module_level`
+    /// of its target, or the level of `Config::new` if none matches. `log::max_level` is
+    /// raised above the level of `Config::new` at `init` if there are module levels or
+    /// always-on targets, so it must be checked here. A `boost_level` raises the level of
+    /// each target to the boosted level, except for the targets whose level is off.
+    fn module_level_enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let raised = !self.config.module_levels.is_empty()
+            || !self.config.always_on_targets.is_empty()
+            || !self.config.level_schedule.is_empty();
+        #[cfg(feature = "regex")]
+        let raised = raised || !self.config.target_regexes.is_empty();
+        if !raised {
+            return true;
+        }
+        let level = match self.module_level(metadata.target()) {
+            Some(LevelFilter::Off) => return false,
+            level => level.unwrap_or_else(|| {
+                SCHEDULED_LEVEL
+                    .with(Cell::get)
+                    .unwrap_or(self.config.level)
+                    .to_level_filter()
+            }),
+        };
+        let boosted = BOOST.with(Cell::get).map(|(_, boosted, _)| boosted);
+        metadata.level() <= boosted.map_or(level, |boosted| level.max(boosted))
+    }
+
+    /// Whether the target rules let the records of `target` through. An empty target only
+    /// passes if there are no target rules.
+    fn target_enabled(&self, target: &str) -> bool {
+        #[cfg(test)]
+        tests::TARGET_MATCHES.with(|matches| matches.set(matches.get() + 1));
+        let exact_targets = &self.config.exact_targets;
+        #[cfg(feature = "regex")]
+        if self
+            .config
+            .target_regexes
+            .iter()
+            .any(|(regex, _)| regex.is_match(target))
+        {
+            return true;
+        }
+        if target.is_empty() {
+            return self.config.module_prefix.is_none() && exact_targets.is_empty();
+        }
+        match self.config.module_prefix {
+            None if exact_targets.is_empty() => true,
+            Some(ref prefix) if matches_module_prefix(target, prefix) => true,
+            _ => exact_targets.iter().any(|exact| exact == target),
+        }
+    }
+
+    /// Render `record` with the current style, and `message_css` as the style of its
+    /// message if any. With `Config::elide_repeated_timestamps`, the timestamp of the entry
+    /// is also returned, to keep with `WasmLogger::emitted` if the entry is emitted.
+    fn render(
+        &self,
+        record: &Record<'_>,
+        message_css: Option<&str>,
+    ) -> (RenderedLog, Option<String>) {
+        let style = self.style.read().unwrap_or_else(|e| e.into_inner());
+        if self.config.elide_repeated_timestamps {
+            let last = self
+                .last_timestamp
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            let elision = TimestampElision {
+                last,
+                rendered: None,
+            };
+            TIMESTAMP_ELISION.with(|cell| *cell.borrow_mut() = Some(elision));
+        }
+        let rendered = match message_css {
+            Some(css) => self.config.render(&style.with_message_style(css), record),
+            None => self.config.render(&style, record),
+        };
+        let timestamp = TIMESTAMP_ELISION
+            .with(|cell| cell.borrow_mut().take())
+            .and_then(|elision| elision.rendered);
+        (rendered, timestamp)
+    }
+
+    /// Keep `timestamp`, the one of the entry which is emitted, for
+    /// `Config::elide_repeated_timestamps`
+    fn emitted(&self, timestamp: Option<String>) {
+        if self.config.elide_repeated_timestamps {
+            *self
+                .last_timestamp
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = timestamp;
+        }
+    }
+
+    fn set_color_scheme(&self, scheme: ColorScheme) {
+        *self.style.write().unwrap_or_else(|e| e.into_inner()) = Style::new(&self.config, scheme);
+    }
+
+    /// Pick the colors for the current theme and follow its changes
+    fn watch_color_scheme(&'static self) {
+        let query = match window()
+            .and_then(|window| window.match_media("(prefers-color-scheme: dark)").ok())
+            .flatten()
+        {
+            Some(query) => query,
+            None => return,
+        };
+        let scheme_of = |query: &web_sys::MediaQueryList| {
+            if query.matches() {
+                ColorScheme::Dark
+            } else {
+                ColorScheme::Light
+            }
+        };
+        self.set_color_scheme(scheme_of(&query));
+        let watched = query.clone();
+        let callback = Closure::<dyn FnMut(web_sys::Event)>::new(move |_| {
+            self.set_color_scheme(scheme_of(&watched));
+        });
+        add_listener(query.into(), "change", callback);
+    }
+
+    /// Report `target` if its entry, which started at `start`, took longer than `budget`
+    /// microseconds, see `Config::warn_if_slow`
+    fn check_duration(&self, target: &str, start: f64, budget: u32) {
+        let micros = match performance_now() {
+            Some(now) => (now - start) * 1000.0,
+            None => return,
+        };
+        if micros > budget as f64
+            && SLOW_TARGETS.with(|targets| targets.borrow_mut().insert(target.to_string()))
+        {
+            internal_log(
+                self.config.internal_log_method,
+                &format!(
+                    "wasm-logger: an entry of `{}` took {:.0}µs to log, over the budget of {}µs",
+                    target, micros, budget
+                ),
+            );
+        }
+    }
+
+    /// The children of the group of `record`, see `Config::rich_grouping`
+    fn metadata_lines(&self, record: &Record<'_>) -> Vec<String> {
+        let mut lines = vec![format!("target: {}", self.config.display_target(record))];
+        if let Some(module) = record.module_path() {
+            lines.push(format!("module: {}", module));
+        }
+        if let Some(file) = record.file() {
+            let line = record
+                .line()
+                .map_or_else(|| "[Unknown]".to_string(), |line| line.to_string());
+            lines.push(format!(
+                "location: {}:{}",
+                self.config.stripped_path(file),
+                line
+            ));
+        }
+        if let Some(dt) = self.config.timestamp(record) {
+            lines.push(format!("timestamp: {}", dt.to_rfc3339()));
+        }
+        for (key, value) in &self.config.global_fields {
+            lines.push(format!("{}: {}", key, value));
+        }
+        #[cfg(feature = "kv")]
+        {
+            let mut pairs = KvLines(
+                Vec::new(),
+                self.config.kv_max_depth,
+                self.config.kv_value_max_len,
+            );
+            let _ = log::kv::Source::visit(record.key_values(), &mut pairs);
+            lines.extend(pairs.0);
+        }
+        lines
+    }
+
+    /// An owned copy of `record` for `Config::on_record`
+    fn record_data(&self, record: &Record<'_>) -> RecordData {
+        #[cfg(feature = "kv")]
+        let key_values = {
+            let mut pairs = KvPairs(Vec::new());
+            let _ = log::kv::Source::visit(record.key_values(), &mut pairs);
+            pairs.0
+        };
+        #[cfg(not(feature = "kv"))]
+        let key_values = Vec::new();
+        RecordData {
+            level: record.level(),
+            target: self.config.display_target(record).to_string(),
+            file: record.file().map(String::from),
+            line: record.line(),
+            timestamp_millis: self
+                .config
+                .timestamp(record)
+                .map_or_else(|| js_sys::Date::now() as i64, |dt| dt.timestamp_millis()),
+            message: record.args().to_string(),
+            key_values,
+        }
+    }
+
+    /// Post `rendered` on the `Config::broadcast_channel`, if any
+    fn broadcast(&self, record: &Record<'_>, rendered: &RenderedLog) {
+        BROADCAST.with(|broadcast| {
+            if let Some((channel, tab)) = &*broadcast.borrow() {
+                let payload = js_sys::Object::new();
+                let fields = [
+                    ("tab", tab.as_str()),
+                    ("level", record.level().as_str()),
+                    ("target", self.config.display_target(record)),
+                    ("message", &rendered.plain_text()),
+                ];
+                for (key, value) in fields {
+                    let _ = js_sys::Reflect::set(&payload, &key.into(), &value.into());
+                }
+                let _ = channel.post_message(&payload);
+            }
+        })
+    }
+
+    /// Dispatch `rendered` as a `CustomEvent`, see `Config::dispatch_dom_events`
+    fn dispatch_dom_event(&self, record: &Record<'_>, rendered: &RenderedLog) {
+        let (selector, event_name) = match self.config.dom_events {
+            Some(ref dom_events) => dom_events,
+            None => return,
+        };
+        // A worker has no document, the event is dispatched on its global object
+        let target: web_sys::EventTarget = window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.query_selector(selector).ok().flatten())
+            .map_or_else(|| js_sys::global().unchecked_into(), Into::into);
+        let detail = js_sys::Object::new();
+        let fields = [
+            ("level", record.level().as_str()),
+            ("target", self.config.display_target(record)),
+            ("message", &rendered.plain_text()),
+        ];
+        for (key, value) in fields {
+            let _ = js_sys::Reflect::set(&detail, &key.into(), &value.into());
+        }
+        let init = web_sys::CustomEventInit::new();
+        init.set_detail(&detail);
+        if let Ok(event) = web_sys::CustomEvent::new_with_event_init_dict(event_name, &init) {
+            let _ = target.dispatch_event(&event);
+        }
+    }
+
+    /// Add `record` as a breadcrumb of the reporter, see `Config::breadcrumbs`
+    fn add_breadcrumb(&self, record: &Record<'_>) {
+        let global = match self.config.breadcrumbs {
+            Some((ref global, min_level)) if record.level() <= min_level => global,
+            _ => return,
+        };
+        let global_object: JsValue = js_sys::global().into();
+        let reporter = global.split('.').try_fold(global_object, |object, name| {
+            js_sys::Reflect::get(&object, &JsValue::from_str(name))
+                .ok()
+                .filter(|value| value.is_object())
+        });
+        let reporter = match reporter {
+            Some(reporter) => reporter,
+            None => return,
+        };
+        let method_name = JsValue::from_str(&self.config.breadcrumb_method);
+        let method = match js_sys::Reflect::get(&reporter, &method_name) {
+            Ok(method) if method.is_function() => method.unchecked_into::<js_sys::Function>(),
+            _ => return,
+        };
+        let breadcrumb = js_sys::Object::new();
+        let level = match record.level() {
+            Level::Error => "error",
+            Level::Warn => "warning",
+            Level::Info => "info",
+            Level::Debug | Level::Trace => "debug",
+        };
+        let fields = [
+            ("level", level),
+            ("message", &record.args().to_string()),
+            ("category", self.config.display_target(record)),
+        ];
+        for (key, value) in fields {
+            let _ = js_sys::Reflect::set(&breadcrumb, &key.into(), &value.into());
+        }
+        let _ = method.call1(&reporter, &breadcrumb);
+    }
+
+    /// Call the console method which corresponds to `level`
+    fn console_log(&self, level: Level, args: &js_sys::Array) {
+        if self.config.unix_streams && is_node() {
+            let method = match level {
+                Level::Error | Level::Warn => ConsoleMethod::Error,
+                Level::Info | Level::Debug | Level::Trace => ConsoleMethod::Log,
+            };
+            method.call(args);
+            return;
+        }
+        if let Some(severity) = self.config.severity(level) {
+            severity.console_method().call(args);
+            return;
+        }
+        let method = match level {
+            Level::Trace if self.config.trace_with_stack => ConsoleMethod::Trace,
+            Level::Trace => ConsoleMethod::Debug,
+            Level::Debug if self.config.debug_uses_console_debug => ConsoleMethod::Debug,
+            Level::Debug => ConsoleMethod::Log,
+            Level::Info if self.config.quiet_info => ConsoleMethod::Log,
+            Level::Info => ConsoleMethod::Info,
+            Level::Warn => ConsoleMethod::Warn,
+            Level::Error => ConsoleMethod::Error,
+        };
+        method.call(args);
+    }
+}
+
+/// The rendering of the entries, which only depends on the config
+impl Config {
+    /// `rendered` as decided by the `Config::pre_emit` hook, or `None` to drop it
+    fn apply_pre_emit(&self, level: Level, rendered: RenderedLog) -> Option<RenderedLog> {
+        match self
+            .pre_emit
+            .as_ref()
+            .map_or(EmitDecision::Emit, |hook| hook(level, &rendered))
+        {
+            EmitDecision::Emit => Some(rendered),
+            EmitDecision::EmitModified(modified) => Some(modified),
+            EmitDecision::Drop => None,
+        }
+    }
+
+    /// Whether `record` is dropped by `EmptyMessagePolicy::Skip`
+    fn skips(&self, record: &Record<'_>) -> bool {
+        matches!(self.empty_message, EmptyMessagePolicy::Skip)
+            && record
+                .args()
+                .as_str()
+                .map_or_else(|| record.args().to_string().is_empty(), str::is_empty)
+    }
+
+    /// The `Config::severity_override` of `level`, if any
+    fn severity(&self, level: Level) -> Option<Severity> {
+        self.severity_overrides
+            .iter()
+            .find(|(overridden, _)| *overridden == level)
+            .map(|(_, severity)| *severity)
+    }
+
+    /// Render `record` with the `Config::formatter`, or in the configured layout, with
+    /// the colors of `style` and the changes of the `Config::dynamic` hook
+    fn render(&self, style: &Style, record: &Record<'_>) -> RenderedLog {
+        let hook = match self.dynamic {
+            Some(ref hook) => hook,
+            None => return self.render_formatted(style, record),
+        };
+        let mut effective = EffectiveConfig {
+            message_location: self.message_location,
+            show_timestamp: true,
+            show_location: true,
+            styled: true,
+            message_css: None,
+        };
+        hook(record, &mut effective);
+        let message_style = effective
+            .message_css
+            .as_deref()
+            .map(|css| style.with_message_style(css));
+        LAYOUT_OVERRIDE.with(|layout| {
+            layout.set(Some(LayoutOverride {
+                message_location: effective.message_location,
+                timestamp: effective.show_timestamp,
+                location: effective.show_location,
+            }))
+        });
+        let rendered = self.render_formatted(message_style.as_ref().unwrap_or(style), record);
+        LAYOUT_OVERRIDE.with(|layout| layout.set(None));
+        if effective.styled {
+            rendered
+        } else {
+            RenderedLog {
+                text: rendered.plain_text(),
+                styles: Vec::new(),
+            }
+        }
+    }
+
+    /// Render `record` with the `Config::formatter`, or in the configured layout, with
+    /// the colors of `style`
+    fn render_formatted(&self, style: &Style, record: &Record<'_>) -> RenderedLog {
+        match self.formatter {
+            Some(ref formatter) => formatter.format(
+                record,
+                &FormatContext {
+                    config: self,
+                    style,
+                },
+            ),
+            None => self.render_builtin(style, record),
+        }
+    }
+
+    /// Render `record` in the configured layout, with the colors of `style`
+    fn render_builtin(&self, style: &Style, record: &Record<'_>) -> RenderedLog {
+        match self.output_format {
+            OutputFormat::Styled | OutputFormat::EnvLogger => {}
+            #[cfg(feature = "json")]
+            OutputFormat::Json => {
+                return RenderedLog {
+                    text: json_line(
+                        record,
+                        self.display_target(record),
+                        self.schema_version,
+                        self.timestamp(record),
+                        &self.global_fields,
+                    ),
+                    styles: Vec::new(),
+                };
+            }
+            OutputFormat::JsonLite => {
+                return RenderedLog {
+                    text: json_lite_line(
+                        record,
+                        self.display_target(record),
+                        self.schema_version,
+                        self.timestamp(record),
+                        &self.global_fields,
+                    ),
+                    styles: Vec::new(),
+                };
+            }
+            OutputFormat::Logfmt => {
+                return RenderedLog {
+                    text: logfmt_line(record, self.timestamp(record), self),
+                    styles: Vec::new(),
+                };
+            }
+        }
+        let layout = LAYOUT_OVERRIDE.with(Cell::get).unwrap_or(LayoutOverride {
+            message_location: self.message_location,
+            timestamp: true,
+            location: true,
+        });
+        let message_separator = match layout.message_location {
+            MessageLocation::NewLine => format!("\n{}", self.newline_indent),
+            MessageLocation::SameLine => " ".to_string(),
+        };
+        let timestamp = self
+            .timestamp_format
+            .as_ref()
+            .filter(|_| layout.timestamp)
+            .filter(|_| {
+                self.timestamp_levels
+                    .as_ref()
+                    .is_none_or(|levels| levels.contains(&record.level()))
+            })
+            .and_then(|ts_fmt| match ts_fmt {
+                TimestampFormat::Rfc2822 => self.timestamp(record).map(|dt| dt.to_rfc2822()),
+                TimestampFormat::Rfc3339 => self.timestamp(record).map(|dt| dt.to_rfc3339()),
+                TimestampFormat::Custom(_) => {
+                    let items = self.timestamp_items.as_ref()?;
+                    self.timestamp(record)
+                        .map(|dt| dt.format_with_items(items.iter()).to_string())
+                }
+                TimestampFormat::TimeOnly => self
+                    .timestamp(record)
+                    .map(|dt| dt.format("%H:%M:%S%.3f").to_string()),
+                TimestampFormat::Performance => performance_now()
+                    .map(|millis| format!("{:.*}", self.performance_precision as usize, millis)),
+            });
+        let timestamp = match timestamp {
+            Some(timestamp) => TIMESTAMP_ELISION.with(|elision| match &mut *elision.borrow_mut() {
+                Some(elision) if self.elide_repeated_timestamps => {
+                    let shown = if elision.last.as_ref() == Some(&timestamp) {
+                        " ".repeat(timestamp.chars().count() + 1)
+                    } else {
+                        format!("{timestamp} ")
+                    };
+                    elision.rendered = Some(timestamp);
+                    shown
+                }
+                _ => format!("{timestamp} "),
+            }),
+            None => String::new(),
+        };
+        let level = match self.level_display {
+            LevelDisplay::Full => record.level().as_str(),
+            LevelDisplay::Initial => &record.level().as_str()[..1],
+        };
+        let level = match self.level_case {
+            Case::Upper => level.to_string(),
+            Case::Lower => level.to_lowercase(),
+            Case::Title => level[..1].to_string() + &level[1..].to_lowercase(),
+        };
+        let file = match (record.file(), &self.location_fallback) {
+            (Some(file), _) => Some(self.stripped_path(file)),
+            (None, LocationFallback::Target) => Some(self.display_target(record)),
+            (None, LocationFallback::ModulePath) => Some(
+                record
+                    .module_path()
+                    .unwrap_or_else(|| self.display_target(record)),
+            ),
+            (None, LocationFallback::Hidden) => None,
+            (None, LocationFallback::Synthetic(location)) => Some(location.as_str()),
+        };
+        let location = file
+            .filter(|_| layout.location)
+            .map_or_else(String::new, |file| {
+                format!(
+                    "{}:{}",
+                    file,
+                    record
+                        .line()
+                        .map_or_else(|| "[Unknown]".to_string(), |line| line.to_string()),
+                )
+            });
+        let mut message = record.args().to_string();
+        if let (true, EmptyMessagePolicy::Placeholder(placeholder)) =
+            (message.is_empty(), &self.empty_message)
+        {
+            message = placeholder.clone();
+        }
+        if self.sanitize_control_chars {
+            message = escape_control_chars(&message);
+        }
+        #[cfg(feature = "regex")]
+        let message_style = self
+            .highlights
+            .iter()
+            .find(|(regex, _)| regex.is_match(&message))
+            .map_or(style.args.as_str(), |(_, css)| css.as_str());
+        #[cfg(not(feature = "regex"))]
+        let message_style = style.args.as_str();
+        if let Some(separator) = self.thousands_separator {
+            message = group_thousands(&message, separator);
+        }
+        if let Some(max_token_len) = self.max_token_len {
+            message = collapse_long_tokens(&message, max_token_len);
+        }
+        if let Some(max_len) = self.max_message_len {
+            message = truncate_message(
+                message,
+                max_len,
+                self.truncate_mode,
+                &self.truncation_indicator,
+            );
+        }
+        message = format!(
+            "{}{}{}{}",
+            " ".repeat(INDENT_DEPTH.with(Cell::get) * self.indent_width),
+            self.message_prefix,
+            message,
+            self.message_suffix
+        );
+        if self.show_global_fields {
+            message.push_str(&self.global_fields_text());
+        }
+        if self.stack_trace_on_error && record.level() == Level::Error {
+            if let Some(stack) = self.captured_stack() {
+                message.push('\n');
+                message.push_str(&stack);
+            }
+        }
+        let level_style = style.level(record.level());
+        let target_style = self.target_style(record.target(), &style.tgt);
+        let mut emoji = if self.level_emoji_prefix {
+            format!("{} ", self.level_emojis[record.level() as usize - 1])
+        } else {
+            String::new()
+        };
+        if self.show_frame {
+            emoji.insert_str(0, &format!("[f:{}] ", FRAME.load(Ordering::Relaxed)));
+        }
+        let mut lead = String::new();
+        let mut segments = Vec::new();
+        let context = self
+            .contextual_style
+            .as_ref()
+            .and_then(|css| context_path().map(|path| (css.as_str(), format!("{} ", path))));
+        let instance_css = self.instance_tag.as_ref().map(|seed| {
+            format!(
+                "font-weight: bold; color: hsl({}, 65%, 45%)",
+                fnv1a(seed) % 360
+            )
+        });
+        let instance = instance_css
+            .as_deref()
+            .zip(self.instance_tag.as_ref())
+            .map(|(css, seed)| (css, format!("{} ", seed)));
+        let context_width = instance
+            .iter()
+            .chain(&context)
+            .map(|(_, text)| text.chars().count())
+            .sum::<usize>();
+        segments.extend(instance);
+        segments.extend(context);
+        let header_width = context_width
+            + match self.output_format {
+                OutputFormat::EnvLogger => {
+                    let padding = if let LevelDisplay::Full = self.level_display {
+                        5 - level.len()
+                    } else {
+                        0
+                    };
+                    let target =
+                        format!("{} {}]", " ".repeat(padding), self.display_target(record));
+                    lead = format!("[{}", timestamp);
+                    let width =
+                        lead.chars().count() + level.chars().count() + target.chars().count();
+                    segments.push((level_style, level.to_string()));
+                    segments.push((target_style.as_str(), target));
+                    width
+                }
+                _ => {
+                    let location = format!(" {}{}", timestamp, location);
+                    let width = level.chars().count() + location.chars().count();
+                    segments.push((level_style, level.to_string()));
+                    segments.push((target_style.as_str(), location));
+                    width
+                }
+            };
+        if self.indent_continuation && message.contains('\n') {
+            let width = match layout.message_location {
+                MessageLocation::NewLine => self.newline_indent.chars().count(),
+                MessageLocation::SameLine => {
+                    emoji.chars().count() + header_width + message_separator.len()
+                }
+            };
+            message = message.replace('\n', &format!("\n{}", " ".repeat(width)));
+        }
+        segments.push((message_style, format!("{}{}", message_separator, message)));
+        lead.insert_str(0, &emoji);
+        if self.ansi_colors {
+            // The level and the location are the last segments before the message
+            let level_segment = segments.len() - 3;
+            let codes = [self.ansi_level_code(record.level()), "1".to_string()];
+            for (i, (_, segment)) in segments.into_iter().enumerate() {
+                match i.checked_sub(level_segment).and_then(|i| codes.get(i)) {
+                    Some(code) => lead.push_str(&format!("\x1b[{}m{}\x1b[0m", code, segment)),
+                    None => lead.push_str(&segment),
+                }
+            }
+            return RenderedLog {
+                text: lead,
+                styles: Vec::new(),
+            };
+        }
+        RenderedLog::from_segments(lead, segments, self.max_style_segments)
+    }
+
+    /// The parameters of the ANSI escape sequence of the level badge of `level`, see
+    /// `Config::ansi_colors`
+    fn ansi_level_code(&self, level: Level) -> String {
+        // The closest ANSI colors to the default CSS palette: gray, blue, green, yellow, red
+        let color = match self.severity(level).map_or(level, Severity::color_level) {
+            Level::Trace => 90,
+            Level::Debug => 34,
+            Level::Info => 32,
+            Level::Warn => 33,
+            Level::Error => 31,
+        };
+        match self.style_mode {
+            StyleMode::Badge => format!("97;{}", color + 10),
+            StyleMode::Text => format!("1;{}", color),
+        }
+    }
+
+    /// `rendered` as sent to the remote outputs: without styles, and with the
+    /// `Config::global_field`s even if they are not shown
+    fn remote_entry(&self, rendered: &RenderedLog) -> String {
+        let mut entry = rendered.plain_text();
+        if !self.show_global_fields && self.is_human_readable() {
+            entry.push_str(&self.global_fields_text());
+        }
+        entry
+    }
+
+    /// Whether the entries are rendered in a human-readable layout, rather than as JSON
+    fn is_human_readable(&self) -> bool {
+        match self.output_format {
+            OutputFormat::Styled | OutputFormat::EnvLogger => true,
+            #[cfg(feature = "json")]
+            OutputFormat::Json => false,
+            OutputFormat::JsonLite | OutputFormat::Logfmt => false,
+        }
+    }
+
+    /// The `Config::global_field`s as they follow the message, or an empty string if
+    /// there are none
+    fn global_fields_text(&self) -> String {
+        if self.global_fields.is_empty() {
+            return String::new();
+        }
+        let fields: Vec<_> = self
+            .global_fields
+            .iter()
+            .map(|(key, value)| format!("{}{}{}", key, self.kv_separator, value))
+            .collect();
+        format!(" [{}]", fields.join(&self.kv_pair_separator))
+    }
+
+    /// The target of `record` as shown, see `Config::empty_target_placeholder`
+    fn display_target<'a>(&'a self, record: &'a Record<'_>) -> &'a str {
+        match record.target() {
+            "" => record
+                .module_path()
+                .unwrap_or(&self.empty_target_placeholder),
+            target => target,
+        }
+    }
+
+    /// The time of `record`: the value of its `Config::timestamp_from_kv` entry if it has
+    /// a valid one, the current time otherwise.
+    #[cfg_attr(not(feature = "kv"), allow(unused_variables))]
+    fn timestamp(&self, record: &Record<'_>) -> Option<chrono::DateTime<chrono::Utc>> {
+        #[cfg(feature = "kv")]
+        if let Some(dt) = self.kv_timestamp(record) {
+            return Some(dt);
+        }
+        chrono::DateTime::from_timestamp_millis(js_sys::Date::now() as i64)
+    }
+
+    /// Read the timestamp of `record` from its `Config::timestamp_from_kv` entry,
+    /// as epoch milliseconds or as an RFC 3339 string
+    #[cfg(feature = "kv")]
+    fn kv_timestamp(&self, record: &Record<'_>) -> Option<chrono::DateTime<chrono::Utc>> {
+        let key = self.timestamp_kv.as_ref()?;
+        let value = record.key_values().get(log::kv::Key::from_str(key))?;
+        if let Some(millis) = value.to_i64() {
+            return chrono::DateTime::from_timestamp_millis(millis);
+        }
+        if let Some(millis) = value.to_f64() {
+            return chrono::DateTime::from_timestamp_millis(millis as i64);
+        }
+        let s = value.to_borrowed_str()?;
+        chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+    }
+
+    /// The style of the location of an entry with `target`, according to
+    /// `Config::target_color` and `Config::color_by_target`
+    fn target_style(&self, target: &str, base: &str) -> String {
+        let rule = self
+            .target_colors
+            .iter()
+            .filter(|(prefix, _)| matches_module_prefix(target, prefix))
+            .max_by_key(|(prefix, _)| prefix.len());
+        if let Some((_, css)) = rule {
+            format!("{}; {}", base, css)
+        } else if self.color_by_target {
+            format!("{}; color: hsl({}, 65%, 45%)", base, fnv1a(target) % 360)
+        } else {
+            base.to_string()
+        }
+    }
+
+    /// Remove the longest matching `Config::strip_path_prefix` from `file`
+    fn stripped_path<'a>(&self, file: &'a str) -> &'a str {
+        self.strip_path_prefixes
+            .iter()
+            .filter_map(|prefix| file.strip_prefix(prefix.as_str()))
+            .min_by_key(|stripped| stripped.len())
+            .unwrap_or(file)
+    }
+
+    /// Capture the current JavaScript stack trace, without the frames matching
+    /// `Config::trim_stack_frames`
+    fn captured_stack(&self) -> Option<String> {
+        let error = js_sys::Error::new("");
+        // The header line of an `Error` created with an empty message is `Error`
+        error_stack(&error, "Error", &self.trim_stack_frames)
+    }
+}
+
+/// The `stack` of `error`, without its `header` line, which some browsers put first, and
+/// the frames which contain any of `trim_patterns`
+fn error_stack(error: &JsValue, header: &str, trim_patterns: &[String]) -> Option<String> {
+    let stack = js_sys::Reflect::get(error, &JsValue::from_str("stack"))
+        .ok()?
+        .as_string()?;
+    let frames: Vec<&str> = stack
+        .lines()
+        .filter(|line| line.trim() != header)
+        .filter(|line| {
+            !trim_patterns
+                .iter()
+                .any(|pattern| line.contains(pattern.as_str()))
+        })
+        .collect();
+    Some(frames.join("\n"))
+}
+
+/// Collects the key-values of a record as `key: value` lines, truncated to a maximum depth
+#[cfg(feature = "kv")]
+struct KvLines(Vec<String>, usize, Option<usize>);
+
+#[cfg(feature = "kv")]
+impl<'kvs> log::kv::VisitSource<'kvs> for KvLines {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let value = truncate_kv_value(truncate_depth(&value.to_string(), self.1), self.2);
+        self.0.push(format!("{}: {}", key, value));
+        Ok(())
+    }
+}
+
+/// Collects the key-values of a record as formatted pairs, see `Config::on_record`
+#[cfg(feature = "kv")]
+struct KvPairs(Vec<(String, String)>);
+
+#[cfg(feature = "kv")]
+impl<'kvs> log::kv::VisitSource<'kvs> for KvPairs {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+/// Collects the key-values of a record as typed JSON values, see `Config::json_format`
+#[cfg(all(feature = "json", feature = "kv"))]
+struct JsonFields<'a>(&'a mut serde_json::Map<String, serde_json::Value>);
+
+#[cfg(all(feature = "json", feature = "kv"))]
+impl<'kvs> log::kv::VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let mut json = JsonValue(serde_json::Value::Null);
+        value.visit(&mut json)?;
+        self.0.insert(key.to_string(), json.0);
+        Ok(())
+    }
+}
+
+/// Converts a key-value to the JSON value of its type, e.g. a number rather than its
+/// text. The integers which do not fit in 64 bits and the non-finite floats, which JSON
+/// cannot represent, are kept as strings.
+#[cfg(all(feature = "json", feature = "kv"))]
+struct JsonValue(serde_json::Value);
+
+#[cfg(all(feature = "json", feature = "kv"))]
+impl<'v> log::kv::VisitValue<'v> for JsonValue {
+    fn visit_any(&mut self, value: log::kv::Value) -> Result<(), log::kv::Error> {
+        self.0 = serde_json::Value::String(value.to_string());
+        Ok(())
+    }
+
+    fn visit_null(&mut self) -> Result<(), log::kv::Error> {
+        self.0 = serde_json::Value::Null;
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), log::kv::Error> {
+        self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), log::kv::Error> {
+        self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_u128(&mut self, value: u128) -> Result<(), log::kv::Error> {
+        self.0 = match <u64 as std::convert::TryFrom<u128>>::try_from(value) {
+            Ok(value) => value.into(),
+            Err(_) => value.to_string().into(),
+        };
+        Ok(())
+    }
+
+    fn visit_i128(&mut self, value: i128) -> Result<(), log::kv::Error> {
+        self.0 = match <i64 as std::convert::TryFrom<i128>>::try_from(value) {
+            Ok(value) => value.into(),
+            Err(_) => value.to_string().into(),
+        };
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), log::kv::Error> {
+        self.0 = serde_json::Number::from_f64(value)
+            .map_or_else(|| value.to_string().into(), serde_json::Value::Number);
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), log::kv::Error> {
+        self.0 = value.into();
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), log::kv::Error> {
+        self.0 = value.into();
+        Ok(())
+    }
+}
+
+/// Replace the control characters of `message`, except `\n` and `\t`, with `\xHH`, or
+/// `\u{HHHH}` out of ASCII
+fn escape_control_chars(message: &str) -> String {
+    let mut escaped = String::with_capacity(message.len());
+    for c in message.chars() {
+        match c {
+            '\n' | '\t' => escaped.push(c),
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:04x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Replace the whitespace-delimited tokens of `message` longer than `max_token_len`
+/// characters with `<token:LEN chars>`
+fn collapse_long_tokens(message: &str, max_token_len: usize) -> String {
+    let mut collapsed = String::with_capacity(message.len());
+    for piece in message.split_inclusive(char::is_whitespace) {
+        let token = piece.trim_end_matches(char::is_whitespace);
+        let len = token.chars().count();
+        if len > max_token_len {
+            collapsed.push_str(&format!("<token:{} chars>", len));
+            collapsed.push_str(&piece[token.len()..]);
+        } else {
+            collapsed.push_str(piece);
+        }
+    }
+    collapsed
+}
+
+/// Keep `max_len` characters of `message`, as picked by `mode`, with `indicator` in place
+/// of the cut
+fn truncate_message(
+    message: String,
+    max_len: usize,
+    mode: TruncateMode,
+    indicator: &str,
+) -> String {
+    let len = message.chars().count();
+    if len <= max_len {
+        return message;
+    }
+    let (head, tail) = match mode {
+        TruncateMode::Head => (max_len, 0),
+        TruncateMode::Tail => (0, max_len),
+        TruncateMode::Middle => (max_len - max_len / 2, max_len / 2),
+    };
+    let mut truncated: String = message.chars().take(head).collect();
+    truncated.push_str(indicator);
+    truncated.extend(message.chars().skip(len - tail));
+    truncated
+}
+
+/// Keep the first `max_len` characters of a key-value, see `Config::kv_value_max_len`
+#[cfg(feature = "kv")]
+fn truncate_kv_value(value: String, max_len: Option<usize>) -> String {
+    match max_len {
+        Some(max_len) => truncate_message(value, max_len, TruncateMode::Head, "…"),
+        None => value,
+    }
+}
+
+/// Replace the content of the brackets of `s` nested deeper than `max_depth` with `…`.
+/// Brackets within double-quoted strings are not counted.
+#[cfg(feature = "kv")]
+fn truncate_depth(s: &str, max_depth: usize) -> String {
+    let mut truncated = String::with_capacity(s.len());
+    let mut depth: usize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in s.chars() {
+        let opening = !in_string && matches!(c, '{' | '[' | '(');
+        let closing = !in_string && matches!(c, '}' | ']' | ')');
+        if closing {
+            depth = depth.saturating_sub(1);
+        }
+        if depth <= max_depth {
+            truncated.push(c);
+            if opening && depth == max_depth {
+                truncated.push('…');
+            }
+        }
+        if opening {
+            depth += 1;
+        }
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        }
+    }
+    truncated
+}
+
+/// Render `record` as a single-line JSON object
+#[cfg(feature = "json")]
+fn json_line(
+    record: &Record<'_>,
+    target: &str,
+    version: u32,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    fields: &[(String, String)],
+) -> String {
+    #[cfg_attr(not(feature = "kv"), allow(unused_mut))]
+    let mut fields: serde_json::Map<_, _> = fields
+        .iter()
+        .map(|(key, value)| (key.clone(), serde_json::Value::from(value.as_str())))
+        .collect();
+    #[cfg(feature = "kv")]
+    let _ = log::kv::Source::visit(record.key_values(), &mut JsonFields(&mut fields));
+    serde_json::json!({
+        "v": version,
+        "timestamp": timestamp.map(|dt| dt.to_rfc3339()),
+        "level": record.level().as_str(),
+        "target": target,
+        "file": record.file(),
+        "line": record.line(),
+        "message": record.args().to_string(),
+        "fields": fields,
+    })
+    .to_string()
+}
+
+/// Render `record` as a compact single-line JSON object, see `Config::json_lite`
+fn json_lite_line(
+    record: &Record<'_>,
+    target: &str,
+    version: u32,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    fields: &[(String, String)],
+) -> String {
+    let mut line = format!("{{\"v\":{},\"ts\":", version);
+    match timestamp {
+        Some(dt) => line.push_str(&dt.timestamp_millis().to_string()),
+        None => line.push_str("null"),
+    }
+    line.push_str(",\"lvl\":");
+    push_json_string(&mut line, record.level().as_str());
+    line.push_str(",\"target\":");
+    push_json_string(&mut line, target);
+    line.push_str(",\"msg\":");
+    push_json_string(&mut line, &record.args().to_string());
+    if !fields.is_empty() {
+        line.push_str(",\"fields\":{");
+        for (i, (key, value)) in fields.iter().enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            push_json_string(&mut line, key);
+            line.push(':');
+            push_json_string(&mut line, value);
+        }
+        line.push('}');
+    }
+    line.push('}');
+    line
+}
+
+/// Render `record` as logfmt pairs, see `Config::logfmt`
+fn logfmt_line(
+    record: &Record<'_>,
+    timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    config: &Config,
+) -> String {
+    let needs_quotes = |c: char| c <= ' ' || c == '=' || c == '"' || c.is_control();
+    let mut line = String::new();
+    let mut push_pair = |key: &str, value: &str| {
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        if key.is_empty() {
+            line.push('_');
+        }
+        line.extend(key.chars().map(|c| if needs_quotes(c) { '_' } else { c }));
+        line.push('=');
+        if value.is_empty() || value.chars().any(needs_quotes) {
+            push_json_string(&mut line, value);
+        } else {
+            line.push_str(value);
+        }
+    };
+    if let Some(dt) = timestamp {
+        push_pair(
+            "ts",
+            &dt.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        );
+    }
+    push_pair("level", &record.level().as_str().to_lowercase());
+    push_pair("target", config.display_target(record));
+    push_pair("msg", &record.args().to_string());
+    for (key, value) in &config.global_fields {
+        push_pair(key, value);
+    }
+    #[cfg(feature = "kv")]
+    {
+        let mut pairs = KvPairs(Vec::new());
+        let _ = log::kv::Source::visit(record.key_values(), &mut pairs);
+        for (key, value) in pairs.0 {
+            push_pair(&key, &truncate_kv_value(value, config.kv_value_max_len));
+        }
+    }
+    line
+}
+
+/// Append `s` to `out` as a JSON string literal
+fn push_json_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Split the style marker off `target`: the text after the first single `#` is the CSS of
+/// the message, and `##` stands for a literal `#` in the target. `None` if `target` has no
+/// `#`, so that it is used as is.
+fn split_target_style(target: &str) -> Option<(String, Option<&str>)> {
+    if !target.contains('#') {
+        return None;
+    }
+    let mut unmarked = String::with_capacity(target.len());
+    let mut chars = target.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '#' {
+            unmarked.push(c);
+        } else if target[i + 1..].starts_with('#') {
+            unmarked.push('#');
+            chars.next();
+        } else {
+            return Some((unmarked, Some(&target[i + 1..])));
+        }
+    }
+    Some((unmarked, None))
+}
+
+/// `record` with `target` in place of its target
+fn with_target<'a>(record: &'a Record<'a>, target: &'a str) -> Record<'a> {
+    let mut builder = Record::builder();
+    builder
+        .args(*record.args())
+        .level(record.level())
+        .target(target)
+        .module_path(record.module_path())
+        .file(record.file())
+        .line(record.line());
+    #[cfg(feature = "kv")]
+    builder.key_values(record.key_values());
+    builder.build()
+}
+
+/// Whether `target` is the module `prefix` or one of its submodules: `my_app` matches
+/// `my_app` and `my_app::net`, but not `my_app_utils`
+fn matches_module_prefix(target: &str, prefix: &str) -> bool {
+    match target.strip_prefix(prefix) {
+        Some(rest) => {
+            rest.is_empty() || rest.starts_with("::") || prefix.is_empty() || prefix.ends_with("::")
+        }
+        None => false,
+    }
+}
+
+/// The 32-bit FNV-1a hash of `s`, stable across sessions
+fn fnv1a(s: &str) -> u32 {
+    s.bytes().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Insert `separator` into the standalone integers of `message` with at least 5 digits
+fn group_thousands(message: &str, separator: char) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    // Whether `c` may touch a number without making it part of a word, decimal or time
+    let is_boundary = |c: Option<&char>| {
+        c.is_none_or(|c| {
+            c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | ',' | ';' | '=')
+        })
+    };
+    let mut grouped = String::with_capacity(message.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            grouped.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+        let digits = &chars[start..i];
+        let before = start.checked_sub(1).and_then(|j| chars.get(j));
+        // A full stop ends a sentence, unless a digit follows it
+        let after = match chars.get(i) {
+            Some('.') if !chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) => None,
+            after => after,
+        };
+        if digits.len() >= 5 && digits[0] != '0' && is_boundary(before) && is_boundary(after) {
+            for (j, digit) in digits.iter().enumerate() {
+                if j > 0 && (digits.len() - j).is_multiple_of(3) {
+                    grouped.push(separator);
+                }
+                grouped.push(*digit);
+            }
+        } else {
+            grouped.extend(digits);
+        }
+    }
+    grouped
+}
+
+/// Initialize the logger which the given config. If failed, it will log a message to the the browser
+/// console, with the method configured by `Config::internal_log_method`; `try_init`
+/// returns the reason instead.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::init(wasm_logger::Config::new(log::Level::Debug));
+/// ```
+/// or
+/// ```rust
+/// wasm_logger::init(wasm_logger::Config::new(log::Level::Debug).module_prefix("some::module"));
+/// ```
+pub fn init(config: Config) {
+    let internal_log_method = config.internal_log_method;
+    if let Err(e) = try_init(config) {
+        internal_log(internal_log_method, &e.to_string());
+    }
+}
+
+/// Why `try_init` failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InitError {
+    /// `wasm-logger` is already installed, by `init`, `try_init` or `init_minimal`, e.g. by
+    /// another module of the app, whose config can be merged with this one through
+    /// `register_config`
+    AlreadyInitialized,
+    /// Another implementation of `log::Log` is installed
+    ForeignLogger,
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitError::AlreadyInitialized => write!(f, "wasm-logger: `init` was already called"),
+            InitError::ForeignLogger => {
+                write!(f, "wasm-logger: another logger is already installed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/// Like `init`, but return why the logger could not be installed, e.g. for a library
+/// which installs it unless the app did. The `log` crate only gives a `&dyn Log` for the
+/// installed logger, so it is recognized as ours by its address, which is the one of the
+/// static logger of `init` or `init_minimal`.
+///
+/// ## Examples
+/// ```rust
+/// use wasm_logger::{Config, InitError};
+///
+/// assert_eq!(wasm_logger::try_init(Config::default()), Ok(()));
+/// assert_eq!(
+///     wasm_logger::try_init(Config::default()),
+///     Err(InitError::AlreadyInitialized)
+/// );
+/// ```
+///
+/// ```rust
+/// use log::{Log, Metadata, Record};
+/// use wasm_logger::{Config, InitError};
+///
+/// struct Other;
+///
+/// impl Log for Other {
+///     fn enabled(&self, _: &Metadata<'_>) -> bool {
+///         true
+///     }
+///     fn log(&self, _: &Record<'_>) {}
+///     fn flush(&self) {}
+/// }
+///
+/// log::set_logger(&Other).unwrap();
+/// assert_eq!(wasm_logger::try_init(Config::default()), Err(InitError::ForeignLogger));
+/// ```
+pub fn try_init(config: Config) -> Result<(), InitError> {
+    let internal_log_method = config.internal_log_method;
+    // `BUFFERING_LOGGER` forwards to `LOGGER` once it is set, or to the logger of
+    // `use_config`. It is already installed by `pre_init_buffer`. It is checked before the
+    // logger is built, so that a failed call builds nothing.
+    if PRE_INIT_INSTALLED.load(Ordering::Acquire) {
+        if LOGGER.get().is_some() {
+            return Err(InitError::AlreadyInitialized);
+        }
+    } else if log::set_logger(&BUFFERING_LOGGER).is_err() {
+        return Err(installed_logger_error());
+    }
+    LOGGER
+        .set(Arc::new(WasmLogger::new(config)))
+        .map_err(|_| InitError::AlreadyInitialized)?;
+    // `LOGGER` is never emptied, so its logger lives as long as the session
+    let logger: &'static WasmLogger = match LOGGER.get() {
+        Some(logger) => logger,
+        None => return Err(InitError::AlreadyInitialized),
+    };
+    INITIALIZED.store(true, Ordering::Release);
+    ASSUME_WORKER.store(logger.config.assume_worker, Ordering::Relaxed);
+    log::set_max_level(logger.max_level(ENABLED_LEVELS.load(Ordering::Relaxed)));
+    if logger.config.defer_until_ready {
+        DEFERRED.with(|deferred| *deferred.borrow_mut() = Some(Vec::new()));
+        set_timeout(
+            &Closure::once_into_js(mark_ready),
+            logger.config.ready_timeout_ms,
+        );
+    }
+    if logger.config.adapt_to_color_scheme {
+        logger.watch_color_scheme();
+    }
+    if let Some(name) = &logger.config.broadcast_channel {
+        match web_sys::BroadcastChannel::new(name) {
+            Ok(channel) => {
+                let tab = format!("{:08x}", (js_sys::Math::random() * u32::MAX as f64) as u32);
+                BROADCAST.with(|broadcast| *broadcast.borrow_mut() = Some((channel, tab)));
+            }
+            Err(_) => internal_log(
+                internal_log_method,
+                &format!("wasm-logger: BroadcastChannel `{name}` is not available"),
+            ),
+        }
+    }
+    if let Some((ref url, _)) = logger.config.websocket_sink {
+        open_websocket(url, internal_log_method);
+    }
+    if !logger.config.level_schedule.is_empty() {
+        refresh_level_schedule();
+    }
+    if let Some(millis) = logger.config.flush_interval_ms {
+        let handler = Closure::<dyn FnMut()>::new(move || logger.send_remote());
+        let id = set_interval(handler.as_ref(), millis);
+        FLUSH_INTERVAL.with(|interval| {
+            *interval.borrow_mut() = Some(FlushInterval {
+                id,
+                _handler: handler,
+            })
+        });
+    }
+    replay_pre_init_buffer(logger);
+    if logger.config.log_environment_on_init {
+        log_environment(&logger.config);
+    }
+    if logger.config.log_hardware_info {
+        log_hardware_info();
+    }
+    Ok(())
+}
+
+/// The error of installing a logger over the one which is installed
+fn installed_logger_error() -> InitError {
+    let installed = log::logger() as *const dyn Log as *const ();
+    let ours = [
+        &BUFFERING_LOGGER as *const BufferingLogger as *const (),
+        &MINIMAL_LOGGER as *const MinimalLogger as *const (),
+    ];
+    if ours.contains(&installed) {
+        InitError::AlreadyInitialized
+    } else {
+        InitError::ForeignLogger
+    }
+}
+
+/// Resolve the level of `Config::level_schedule` for the current hour, and again at the
+/// start of the next hour
+fn refresh_level_schedule() {
+    let logger = match active_logger() {
+        Some(logger) => logger,
+        None => return,
+    };
+    let now = js_sys::Date::new_0();
+    let hour = now.get_hours() as u8;
+    SCHEDULED_LEVEL.with(|level| level.set(logger.config.scheduled_level(hour)));
+    let minutes = now.get_minutes() as f64;
+    let seconds = now.get_seconds() as f64 + now.get_milliseconds() as f64 / 1000.0;
+    let to_next_hour = (60.0 - minutes) * 60_000.0 - seconds * 1000.0;
+    set_timeout(
+        &Closure::once_into_js(refresh_level_schedule),
+        to_next_hour.max(0.0) as u32 + 1,
+    );
+}
+
+/// The logger which handles the records: the one of `use_config` if any, or else the one of
+/// `init`, if it was called
+fn active_logger() -> Option<Arc<WasmLogger>> {
+    let active = ACTIVE.read().unwrap_or_else(|e| e.into_inner()).clone();
+    active.or_else(|| LOGGER.get().cloned())
+}
+
+/// Keep `config` under `name`, so that `use_config` can make it the config of the logger,
+/// e.g. to switch between a verbose config to debug the network and the usual one. A
+/// config registered again under the same name replaces the previous one, which is freed
+/// once it is no longer active.
+///
+/// Each config gets its own logger, and `log` keeps a single logger which forwards the
+/// records to the active one, the logger of `init` until `use_config` is called. The
+/// outputs opened by `init`, such as `Config::websocket_sink` or
+/// `Config::broadcast_channel`, and the settings applied by it, such as
+/// `Config::adapt_to_color_scheme`, are those of the config of `init`.
+///
+/// ## Examples
+/// ```rust
+/// use log::Level;
+/// use wasm_logger::Config;
+///
+/// wasm_logger::register_config("network", Config::new(Level::Debug).module_prefix("app::net"));
+/// wasm_logger::register_config("quiet", Config::new(Level::Warn));
+/// assert!(wasm_logger::use_config("network"));
+/// assert!(!wasm_logger::use_config("verbose"));
+/// ```
+pub fn register_config(name: &str, config: Config) {
+    let logger = Arc::new(WasmLogger::new(config));
+    let mut configs = CONFIGS.write().unwrap_or_else(|e| e.into_inner());
+    match configs
+        .iter_mut()
+        .find(|(registered, _)| registered == name)
+    {
+        Some((_, registered)) => *registered = logger,
+        None => configs.push((name.to_string(), logger)),
+    }
+}
+
+/// Handle the next records with the config registered under `name` by `register_config`,
+/// instead of the current one. The config of `init` is only active again with
+/// `use_init_config`. Return `false`, and keep the current config, if no config has this
+/// name.
+pub fn use_config(name: &str) -> bool {
+    let logger = CONFIGS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(registered, _)| registered == name)
+        .map(|(_, logger)| Arc::clone(logger));
+    match logger {
+        Some(logger) => {
+            activate(Some(logger));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Handle the next records with the config of `init` again, after `use_config`
+pub fn use_init_config() {
+    activate(None);
+}
+
+/// Make `logger` the active one, or the logger of `init` if `None`, and apply its maximum
+/// level, unless a `boost_level` is active or the logger is shut down
+fn activate(logger: Option<Arc<WasmLogger>>) {
+    *ACTIVE.write().unwrap_or_else(|e| e.into_inner()) = logger;
+    if let Some(logger) = active_logger() {
+        let boosted = BOOST.with(|boost| boost.get().is_some());
+        if INITIALIZED.load(Ordering::Acquire) && !boosted && !SHUT_DOWN.load(Ordering::Relaxed) {
+            log::set_max_level(logger.max_level(ENABLED_LEVELS.load(Ordering::Relaxed)));
+        }
+    }
+}
+
+/// The maximum number of records which `pre_init_buffer` holds
+const PRE_INIT_BUFFER_CAP: usize = 1000;
+
+/// A record held by `pre_init_buffer`, with its message formatted
+struct BufferedRecord {
+    level: Level,
+    target: String,
+    module_path: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: String,
+}
+
+/// The logger installed in `log`: it holds the records of `pre_init_buffer` until `init`,
+/// then forwards them to the logger of `init`, or of `use_config`
+struct BufferingLogger;
+
+static BUFFERING_LOGGER: BufferingLogger = BufferingLogger;
+
+impl Log for BufferingLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        match active_logger() {
+            Some(logger) => logger.enabled(metadata),
+            None => metadata.level() <= log::max_level(),
+        }
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if let Some(logger) = active_logger() {
+            return logger.log(record);
+        }
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut buffer = PRE_INIT_BUFFER.lock().unwrap_or_else(|e| e.into_inner());
+        if buffer.0.len() < PRE_INIT_BUFFER_CAP {
+            buffer.0.push(BufferedRecord {
+                level: record.level(),
+                target: record.target().to_string(),
+                module_path: record.module_path().map(String::from),
+                file: record.file().map(String::from),
+                line: record.line(),
+                message: record.args().to_string(),
+            });
+        } else {
+            buffer.1 += 1;
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = active_logger() {
+            logger.flush();
+        }
+    }
+}
+
+/// Install a logger right away which holds the records logged before `init`, up to
+/// `level`, so that the first entries of the app, often the most useful to diagnose its
+/// startup, are not dropped by `log` for lack of a logger. Call it first thing, e.g. at
+/// the top of the `#[wasm_bindgen(start)]` function; `init` then logs the held records
+/// through its config, and the records logged after `init` go to it.
+///
+/// - The held records are logged by `init` in the order in which they were logged,
+///   before any record logged after `init` returns. They are filtered by the config of
+///   `init` and get their timestamp when they are replayed; their key-values are lost.
+/// - At most 1000 records are held. The next ones are dropped, and `init` reports their
+///   number with the method of `Config::internal_log_method`.
+/// - `init_minimal` can not be used after it, as the logger is already installed.
+///
+/// It does nothing if a logger is already installed.
+///
+/// ## Examples
+/// ```rust
+/// use log::Level;
+///
+/// wasm_logger::pre_init_buffer(Level::Debug);
+/// log::debug!("held until `init`");
+/// log::trace!("dropped: above the level of `pre_init_buffer`");
+/// assert!(!wasm_logger::is_initialized());
+/// ```
+pub fn pre_init_buffer(level: Level) {
+    if log::set_logger(&BUFFERING_LOGGER).is_ok() {
+        PRE_INIT_INSTALLED.store(true, Ordering::Release);
+        log::set_max_level(level.to_level_filter());
+    }
+}
+
+/// Log the records held by `pre_init_buffer` through `logger`
+fn replay_pre_init_buffer(logger: &WasmLogger) {
+    if !PRE_INIT_INSTALLED.load(Ordering::Acquire) {
+        return;
+    }
+    let (records, dropped) =
+        std::mem::take(&mut *PRE_INIT_BUFFER.lock().unwrap_or_else(|e| e.into_inner()));
+    for record in &records {
+        logger.log(
+            &Record::builder()
+                .level(record.level)
+                .target(&record.target)
+                .module_path(record.module_path.as_deref())
+                .file(record.file.as_deref())
+                .line(record.line)
+                .args(format_args!("{}", record.message))
+                .build(),
+        );
+    }
+    if dropped > 0 {
+        internal_log(
+            logger.config.internal_log_method,
+            &format!("wasm-logger: {dropped} logs before `init` dropped, the buffer was full"),
+        );
+    }
+}
+
+/// The logger of `init_minimal`
+struct MinimalLogger;
+
+static MINIMAL_LOGGER: MinimalLogger = MinimalLogger;
+
+impl Log for MinimalLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level() && !SHUT_DOWN.load(Ordering::Relaxed)
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let (color, method) = match record.level() {
+            Level::Trace => ("gray", ConsoleMethod::Debug),
+            Level::Debug => ("blue", ConsoleMethod::Log),
+            Level::Info => ("green", ConsoleMethod::Info),
+            Level::Warn => ("orange", ConsoleMethod::Warn),
+            Level::Error => ("darkred", ConsoleMethod::Error),
+        };
+        let text = format!(
+            "%c{}%c {}:{}%c {}",
+            record.level(),
+            record.file().unwrap_or_else(|| record.target()),
+            record
+                .line()
+                .map_or_else(|| "[Unknown]".to_string(), |line| line.to_string()),
+            record.args()
+        );
+        method.call(&js_sys::Array::of4(
+            &JsValue::from_str(&text),
+            &JsValue::from_str(&format!(
+                "color: white; padding: 0 3px; background: {};",
+                color
+            )),
+            &JsValue::from_str("font-weight: bold; color: inherit"),
+            &JsValue::from_str("background: inherit; color: inherit"),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install a barebones logger which outputs the `log`s up to `level`, in the default layout
+/// of `Config` (`$LEVEL $FILE_PATH:$LINE_NUMBER $message`) and colors, for the apps which
+/// want the smallest and fastest logger. It does not read a `Config`, so it omits:
+/// - the target rules and module levels, and the runtime filters: `mute_target`, `focus`,
+///   `set_level_enabled` and `boost_level` (which still raises `log::max_level`),
+/// - the timestamps and every option of the format,
+/// - the outputs other than the console, the rate limit and the suppression counters,
+/// - the context of `with_context` and the hooks, such as `Config::pre_emit`,
+/// - the functions which log through the logger of `init`, such as `emit` and `logf`,
+///   which do nothing.
+///
+/// If the app never calls `init`, the code of the full logger is not linked into the
+/// binary. Like `init`, it can only be called once; `shutdown` disables it.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::init_minimal(log::Level::Info);
+/// assert!(wasm_logger::is_initialized());
+/// ```
+pub fn init_minimal(level: Level) {
+    match log::set_logger(&MINIMAL_LOGGER) {
+        Ok(_) => {
+            INITIALIZED.store(true, Ordering::Release);
+            log::set_max_level(level.to_level_filter());
+        }
+        Err(_) => internal_log(
+            Some(ConsoleMethod::Warn),
+            &installed_logger_error().to_string(),
+        ),
+    }
+}
+
+/// The delays before the socket of `Config::websocket_sink` is reopened
+const WEBSOCKET_MIN_BACKOFF_MS: u32 = 500;
+const WEBSOCKET_MAX_BACKOFF_MS: u32 = 30_000;
+
+/// The connection of `Config::websocket_sink`
+struct WebSocketSink {
+    url: String,
+    /// The socket which is connecting or open, if any
+    socket: Option<web_sys::WebSocket>,
+    /// The `open` and `close` handlers of `socket`
+    callbacks: Vec<Closure<dyn FnMut(web_sys::Event)>>,
+    /// The entries waiting for the socket to be open
+    queue: VecDeque<String>,
+    /// The delay before the next reconnection
+    backoff_ms: u32,
+}
+
+impl WebSocketSink {
+    /// Send the queued entries if the socket is open. An entry which can not be sent stays
+    /// queued.
+    fn send_queued(&mut self) {
+        let socket = match self.socket {
+            Some(ref socket) if socket.ready_state() == web_sys::WebSocket::OPEN => socket,
+            _ => return,
+        };
+        while let Some(entry) = self.queue.front() {
+            if socket.send_with_str(entry).is_err() {
+                break;
+            }
+            self.queue.pop_front();
+        }
+    }
+}
+
+/// Start streaming to `url`, see `Config::websocket_sink`
+fn open_websocket(url: &str, internal_log_method: Option<ConsoleMethod>) {
+    let available =
+        js_sys::Reflect::has(&js_sys::global(), &JsValue::from_str("WebSocket")).unwrap_or(false);
+    if !available {
+        internal_log(
+            internal_log_method,
+            "wasm-logger: WebSocket is not available",
+        );
+        return;
+    }
+    WEBSOCKET.with(|sink| {
+        *sink.borrow_mut() = Some(WebSocketSink {
+            url: url.to_string(),
+            socket: None,
+            callbacks: Vec::new(),
+            queue: VecDeque::new(),
+            backoff_ms: WEBSOCKET_MIN_BACKOFF_MS,
+        })
+    });
+    connect_websocket();
+    if WEBSOCKET.with(|sink| sink.borrow().is_none()) {
+        internal_log(
+            internal_log_method,
+            &format!("wasm-logger: invalid WebSocket url `{url}`"),
+        );
+    }
+}
+
+/// Open the socket of `Config::websocket_sink`, and reopen it when it closes
+fn connect_websocket() {
+    if SHUT_DOWN.load(Ordering::Relaxed) {
+        return;
+    }
+    WEBSOCKET.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let sink = match cell.as_mut() {
+            Some(sink) => sink,
+            None => return,
+        };
+        // Only a syntax error of the url fails, which reconnecting would not fix
+        let socket = match web_sys::WebSocket::new(&sink.url) {
+            Ok(socket) => socket,
+            Err(_) => {
+                *cell = None;
+                return;
+            }
+        };
+        let on_open = Closure::<dyn FnMut(web_sys::Event)>::new(|_| {
+            WEBSOCKET.with(|sink| {
+                if let Some(sink) = sink.borrow_mut().as_mut() {
+                    sink.backoff_ms = WEBSOCKET_MIN_BACKOFF_MS;
+                    sink.send_queued();
+                }
+            })
+        });
+        // A failed connection closes the socket too, so `error` needs no handler
+        let on_close = Closure::<dyn FnMut(web_sys::Event)>::new(|_| {
+            let delay = WEBSOCKET.with(|sink| {
+                let mut sink = sink.borrow_mut();
+                let sink = sink.as_mut()?;
+                sink.socket = None;
+                let delay = sink.backoff_ms;
+                sink.backoff_ms = (delay * 2).min(WEBSOCKET_MAX_BACKOFF_MS);
+                Some(delay)
+            });
+            if let Some(delay) = delay {
+                set_timeout(&Closure::once_into_js(connect_websocket), delay);
+            }
+        });
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        sink.socket = Some(socket);
+        // The handlers of the previous socket are not running, since this is called
+        // from a timeout, so they can be dropped
+        sink.callbacks = vec![on_open, on_close];
+    });
+}
+
+/// Queue `entry` for `Config::websocket_sink`, keeping at most `max` entries, and send the
+/// queue if the socket is open. Return the number of entries dropped from the queue.
+fn send_websocket(entry: String, max: usize) -> usize {
+    WEBSOCKET.with(|sink| match sink.borrow_mut().as_mut() {
+        Some(sink) => {
+            let dropped = push_bounded(&mut sink.queue, entry, max);
+            sink.send_queued();
+            dropped
+        }
+        None => 0,
+    })
+}
+
+/// Append `entry` to `buffer`, dropping its oldest entries so that it holds at most `max`,
+/// see `Config::max_buffered_logs`. Return the number of dropped entries.
+fn push_bounded(buffer: &mut VecDeque<String>, entry: String, max: usize) -> usize {
+    buffer.push_back(entry);
+    let mut dropped = 0;
+    while buffer.len() > max {
+        buffer.pop_front();
+        dropped += 1;
+    }
+    SUPPRESSED_BUFFER_OVERFLOW.fetch_add(dropped as u64, Ordering::Relaxed);
+    dropped
+}
+
+/// Call `hook` with the number of lost entries, see `Config::on_delivery_failure`. Like in
+/// `WasmLogger::log_record`, the entries which it logs are dropped.
+fn report_delivery_failure(hook: &DeliveryFailureHook, dropped: usize) {
+    let nested = IN_LOG.with(|in_log| in_log.replace(true));
+    let _guard = (!nested).then_some(InLogGuard);
+    hook(dropped);
+}
+
+/// Close the socket of `Config::websocket_sink` for good, see `shutdown`. Return the
+/// number of entries which were still queued.
+fn close_websocket() -> usize {
+    match WEBSOCKET.with(|sink| sink.borrow_mut().take()) {
+        Some(sink) => {
+            if let Some(socket) = sink.socket {
+                socket.set_onopen(None);
+                socket.set_onclose(None);
+                let _ = socket.close();
+            }
+            sink.queue.len()
+        }
+        None => 0,
+    }
+}
+
+/// The time without entries after which `Config::auto_group_by_target` closes the group
+const AUTO_GROUP_TIMEOUT_MS: f64 = 1000.0;
+
+/// Make sure that the group of `target` is open, see `Config::auto_group_by_target`
+fn open_auto_group(target: &str) {
+    let now = js_sys::Date::now();
+    let opened = AUTO_GROUP.with(|group| match &mut *group.borrow_mut() {
+        Some((open, last, _)) if open == target => {
+            *last = now;
+            None
+        }
+        group => {
+            if group.is_some() {
+                console::group_end();
+            }
+            console::group_collapsed_1(&JsValue::from_str(target));
+            let id = AUTO_GROUP_ID.with(|id| {
+                id.set(id.get().wrapping_add(1));
+                id.get()
+            });
+            *group = Some((target.to_string(), now, id));
+            Some(id)
+        }
+    });
+    if let Some(id) = opened {
+        schedule_auto_group_close(id, AUTO_GROUP_TIMEOUT_MS);
+    }
+}
+
+/// Close the group `id` after `delay` milliseconds, or later if it got entries meanwhile
+fn schedule_auto_group_close(id: u32, delay: f64) {
+    let check = Closure::once_into_js(move || {
+        let remaining = AUTO_GROUP.with(|group| {
+            let mut group = group.borrow_mut();
+            match *group {
+                Some((_, last, current)) if current == id => {
+                    let idle = js_sys::Date::now() - last;
+                    if idle >= AUTO_GROUP_TIMEOUT_MS {
+                        console::group_end();
+                        *group = None;
+                        None
+                    } else {
+                        Some(AUTO_GROUP_TIMEOUT_MS - idle)
+                    }
+                }
+                // Closed or replaced by another group meanwhile
+                _ => None,
+            }
+        });
+        if let Some(remaining) = remaining {
+            schedule_auto_group_close(id, remaining);
+        }
+    });
+    set_timeout(&check, delay.ceil() as u32);
+}
+
+/// Close the group of `Config::auto_group_by_target`, if one is open
+fn close_auto_group() {
+    if AUTO_GROUP.with(|group| group.borrow_mut().take()).is_some() {
+        console::group_end();
+    }
+}
+
+/// The `performance` of the global object, in windows and workers alike
+fn performance() -> Option<web_sys::Performance> {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .ok()
+        .filter(|performance| performance.is_object())
+        .map(JsCast::unchecked_into)
+}
+
+/// `performance.now()`, in milliseconds, if the global object has a `performance`
+fn performance_now() -> Option<f64> {
+    performance().map(|performance| performance.now())
+}
+
+thread_local! {
+    /// Set once `mark` or `measure` reported that `performance` is not available
+    static PERFORMANCE_MISSING_REPORTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The `performance` for `mark` and `measure`, or a one-time report of its absence
+fn performance_for_timeline() -> Option<web_sys::Performance> {
+    let performance = performance();
+    if performance.is_none()
+        && !PERFORMANCE_MISSING_REPORTED.with(|reported| reported.replace(true))
+    {
+        let method = active_logger().map_or(Some(ConsoleMethod::Warn), |logger| {
+            logger.config.internal_log_method
+        });
+        internal_log(
+            method,
+            "wasm-logger: `performance` is not available, marks and measures are ignored",
+        );
+    }
+    performance
+}
+
+/// Add a mark named `name` on the timeline of the devtools Performance panel, with
+/// `performance.mark`, e.g. to correlate the entries with a recording. This is not a `log`
+/// entry, so no filter applies. Does nothing, after a one-time warning, if `performance`
+/// is not available.
+pub fn mark(name: &str) {
+    if let Some(performance) = performance_for_timeline() {
+        let _ = performance.mark(name);
+    }
+}
+
+/// Add a measure named `name` between the marks `start_mark` and `end_mark` of `mark` on
+/// the timeline of the devtools Performance panel, with `performance.measure`. Like `mark`,
+/// it does nothing if `performance` is not available.
+///
+/// ## Examples
+/// ```rust,no_run
+/// wasm_logger::mark("load:start");
+/// // ...
+/// wasm_logger::mark("load:end");
+/// wasm_logger::measure("load", "load:start", "load:end");
+/// ```
+pub fn measure(name: &str, start_mark: &str, end_mark: &str) {
+    if let Some(performance) = performance_for_timeline() {
+        let _ = performance.measure_with_start_mark_and_end_mark(name, start_mark, end_mark);
+    }
+}
+
+/// Log `{label}: {duration}` at `level`, with `millis` in the most readable unit: `µs`,
+/// `ms`, `s` or `min`, as the other [helpers](crate#helpers).
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::log_duration(log::Level::Info, "parse", 1234.5);
+/// wasm_logger::log_duration(log::Level::Debug, "hash", 0.042);
+/// ```
+/// logs:
+/// ```text
+/// parse: 1.23s
+/// hash: 42µs
+/// ```
+#[track_caller]
+pub fn log_duration(level: Level, label: &str, millis: f64) {
+    log_at(
+        level,
+        std::panic::Location::caller(),
+        format_args!("{}: {}", label, human_duration(millis)),
+    );
+}
+
+/// Log `{label}: {size}` at `level`, with `bytes` in the largest unit in which it is at
+/// least 1: `B`, `KiB`, `MiB`, `GiB`, `TiB`, `PiB` or `EiB`, or their decimal counterparts
+/// with `Config::si_units`, as the other [helpers](crate#helpers).
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::log_bytes(log::Level::Info, "bundle", 1_572_864);
+/// wasm_logger::log_bytes(log::Level::Debug, "cache", 239_616);
+/// ```
+/// logs:
+/// ```text
+/// bundle: 1.5 MiB
+/// cache: 234 KiB
+/// ```
+#[track_caller]
+pub fn log_bytes(level: Level, label: &str, bytes: u64) {
+    let si = active_logger().is_some_and(|logger| logger.config.si_units);
+    log_at(
+        level,
+        std::panic::Location::caller(),
+        format_args!("{}: {}", label, human_bytes(bytes, si)),
+    );
+}
+
+/// Log `label` followed by `pairs` as a two-column text table at `level`, with the keys
+/// padded to the longest one, e.g. to copy into a ticket where the widget of
+/// `console.table` can not go. Like any multi-line message, the lines of the table follow
+/// `Config::message_on_new_line` and `Config::indent_continuation`. The entry is logged
+/// as by the other [helpers](crate#helpers).
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::log_kv_table(
+///     log::Level::Info,
+///     "environment",
+///     &[("browser", "Firefox 128"), ("viewport", "1280x720"), ("locale", "fr-FR")],
+/// );
+/// ```
+/// logs:
+/// ```text
+/// environment:
+///   browser   Firefox 128
+///   viewport  1280x720
+///   locale    fr-FR
+/// ```
+#[track_caller]
+pub fn log_kv_table(level: Level, label: &str, pairs: &[(&str, &str)]) {
+    log_at(
+        level,
+        std::panic::Location::caller(),
+        format_args!("{}", KvTable(label, pairs)),
+    );
+}
+
+/// Log a divider line, e.g. `────────`, to split the output into phases. The line is
+/// made of `Config::separator_char`, is `Config::separator_width` characters wide and is
+/// logged in gray at `Config::separator_level`, with the file and line of the caller and
+/// the target `wasm_logger`.
+#[track_caller]
+pub fn separator() {
+    log_separator(std::panic::Location::caller(), None);
+}
+
+/// Log a divider line with `label` in its middle, e.g. `──── loaded ────`, like
+/// `separator`.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::separator_labeled("init");
+/// log::info!("config loaded");
+/// wasm_logger::separator_labeled("user actions");
+/// ```
+#[track_caller]
+pub fn separator_labeled(label: &str) {
+    log_separator(std::panic::Location::caller(), Some(label));
+}
+
+fn log_separator(caller: &std::panic::Location<'_>, label: Option<&str>) {
+    let (c, width, level) = active_logger().map_or(('─', 40, Level::Info), |logger| {
+        let config = &logger.config;
+        (
+            config.separator_char,
+            config.separator_width,
+            config.separator_level,
+        )
+    });
+    if level > log::STATIC_MAX_LEVEL || level > log::max_level() {
+        return;
+    }
+    let line = match label {
+        Some(label) => {
+            let rule = width.saturating_sub(label.chars().count() + 2).max(2);
+            format!(
+                "{} {} {}",
+                c.to_string().repeat(rule / 2),
+                label,
+                c.to_string().repeat(rule - rule / 2)
+            )
+        }
+        None => c.to_string().repeat(width),
+    };
+    log::logger().log(
+        &Record::builder()
+            .level(level)
+            .target("wasm_logger#color: gray")
+            .file(Some(caller.file()))
+            .line(Some(caller.line()))
+            .args(format_args!("{}", line))
+            .build(),
+    );
+}
+
+/// The text table of `log_kv_table`, formatted only if the entry is logged
+struct KvTable<'a>(&'a str, &'a [(&'a str, &'a str)]);
+
+impl std::fmt::Display for KvTable<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .1
+            .iter()
+            .map(|(key, _)| key.chars().count())
+            .max()
+            .unwrap_or(0);
+        write!(f, "{}:", self.0)?;
+        for (key, value) in self.1 {
+            write!(f, "\n  {:<width$}  {}", key, value, width = width)?;
+        }
+        Ok(())
+    }
+}
+
+/// Log `message` at `level` with the console method `method` instead of the method of
+/// `level`, e.g. `console.error` for an info entry which must stand out. The entry is
+/// subject to the level and target filters of `level`, and is styled and delivered to the
+/// other outputs as an entry of `level`. It has the file and line of the caller and the
+/// target `wasm_logger`. With `Config::rich_grouping`, the entry is a group, whose method
+/// can not be chosen.
+///
+/// ## Examples
+/// ```rust
+/// use wasm_logger::{ConsoleMethod, Level};
+///
+/// wasm_logger::log_as(ConsoleMethod::Error, Level::Info, "payment provider switched");
+/// ```
+#[track_caller]
+pub fn log_as(method: ConsoleMethod, level: Level, message: &str) {
+    METHOD_OVERRIDE.with(|method_override| method_override.set(Some(method)));
+    log_at(
+        level,
+        std::panic::Location::caller(),
+        format_args!("{}", message),
+    );
+    // Not taken by the logger if the entry was filtered out by `log::max_level`
+    METHOD_OVERRIDE.with(|method_override| method_override.set(None));
+}
+
+/// Log `args` at `level` with the target `wasm_logger` and the file and line of `caller`,
+/// for the helpers which log on behalf of their caller
+fn log_at(level: Level, caller: &std::panic::Location<'_>, args: std::fmt::Arguments<'_>) {
+    log_at_target(level, "wasm_logger", caller, args);
+}
+
+/// Log `args` at `level` with `target` and the file and line of `caller`
+fn log_at_target(
+    level: Level,
+    target: &str,
+    caller: &std::panic::Location<'_>,
+    args: std::fmt::Arguments<'_>,
+) {
+    if level <= log::STATIC_MAX_LEVEL && level <= log::max_level() {
+        log::logger().log(
+            &Record::builder()
+                .level(level)
+                .target(target)
+                .file(Some(caller.file()))
+                .line(Some(caller.line()))
+                .args(args)
+                .build(),
+        );
+    }
+}
+
+/// Format a number of bytes in the largest unit in which it is at least 1, with a decimal
+/// below 10 units
+fn human_bytes(bytes: u64, si: bool) -> String {
+    let (base, units) = if si {
+        (1000.0, ["B", "kB", "MB", "GB", "TB", "PB", "EB"])
+    } else {
+        (1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"])
+    };
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= base && unit < units.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else if value < 10.0 {
+        format!("{:.1} {}", value, units[unit])
+    } else {
+        format!("{:.0} {}", value, units[unit])
+    }
+}
+
+/// Format a duration in milliseconds in the largest unit in which it is at least 1
+fn human_duration(millis: f64) -> String {
+    match millis.abs() {
+        abs if abs < 1.0 => format!("{:.0}µs", millis * 1000.0),
+        abs if abs < 1000.0 => format!("{:.2}ms", millis),
+        abs if abs < 60_000.0 => format!("{:.2}s", millis / 1000.0),
+        _ => format!("{:.2}min", millis / 60_000.0),
+    }
+}
+
+/// A running timer started by `time_start`, which logs its elapsed time with `log_duration`
+#[derive(Debug, Clone)]
+pub struct TimeToken {
+    label: String,
+    start: f64,
+}
+
+/// Start timing `label` with `performance.now()`, or `Date.now()` if the Performance API is
+/// not available. Unlike `console.time`, the elapsed time is logged by the logger, with its
+/// filters, styles and outputs.
+///
+/// ## Examples
+/// ```rust,no_run
+/// let timer = wasm_logger::time_start("load assets");
+/// // ...
+/// timer.log_elapsed(log::Level::Debug);
+/// ```
+pub fn time_start(label: &str) -> TimeToken {
+    TimeToken {
+        label: label.to_string(),
+        start: performance_now().unwrap_or_else(js_sys::Date::now),
+    }
+}
+
+impl TimeToken {
+    /// The milliseconds elapsed since `time_start`
+    pub fn elapsed_millis(&self) -> f64 {
+        performance_now().unwrap_or_else(js_sys::Date::now) - self.start
+    }
+
+    /// Log the time elapsed since `time_start` with `log_duration`. The timer keeps running,
+    /// so it can be logged again, e.g. for each step of an operation.
+    #[track_caller]
+    pub fn log_elapsed(&self, level: Level) {
+        log_duration(level, &self.label, self.elapsed_millis());
+    }
+}
+
+/// Log `→ {name}` at the debug level, and enter a `with_context` scope named `name` until
+/// the returned guard is dropped, which logs `← {name} ({elapsed})`, e.g. to trace a
+/// request without a tracing framework. The entries logged meanwhile can be tagged with
+/// the scope by `Config::style_contextual`. The names of nested spans are chained, as in
+/// `→ checkout › payment`. Both entries have the file and line of the caller and the
+/// target `wasm_logger`.
+///
+/// ## Examples
+/// ```rust,no_run
+/// fn checkout() {
+///     let _span = wasm_logger::span("checkout");
+///     log::info!("cart validated");
+/// } // logs `← checkout (1.52ms)`
+/// ```
+#[track_caller]
+pub fn span(name: &str) -> SpanGuard {
+    let context = enter_context(name);
+    let guard = SpanGuard {
+        path: context_path().unwrap_or_default(),
+        start: performance_now().unwrap_or_else(js_sys::Date::now),
+        caller: std::panic::Location::caller(),
+        _context: context,
+    };
+    log_at(Level::Debug, guard.caller, format_args!("→ {}", guard.path));
+    guard
+}
+
+/// The scope of a `span`, which logs its end when dropped
+#[must_use = "the span ends when the guard is dropped"]
+pub struct SpanGuard {
+    path: String,
+    start: f64,
+    caller: &'static std::panic::Location<'static>,
+    /// Dropped after `SpanGuard::drop` has logged the end of the span inside its scope
+    _context: ContextGuard,
+}
+
+impl Drop for SpanGuard {
+    fn drop(&mut self) {
+        let elapsed = performance_now().unwrap_or_else(js_sys::Date::now) - self.start;
+        log_at(
+            Level::Debug,
+            self.caller,
+            format_args!("← {} ({})", self.path, human_duration(elapsed)),
+        );
+    }
+}
+
+/// Log the environment of the app, see `Config::log_environment_on_init`
+fn log_environment(config: &Config) {
+    let user_agent = navigator_property("userAgent").and_then(|user_agent| user_agent.as_string());
+    let viewport = window().and_then(|window| {
+        let width = window.inner_width().ok()?.as_f64()?;
+        let height = window.inner_height().ok()?.as_f64()?;
+        Some(format!("{}x{}", width, height))
+    });
+    log::info!(
+        target: "wasm_logger",
+        "wasm-logger {}\nuser agent: {}\nviewport: {}\n{}",
+        env!("CARGO_PKG_VERSION"),
+        user_agent.as_deref().unwrap_or("unknown"),
+        viewport.as_deref().unwrap_or("unknown"),
+        describe(config),
+    );
+}
+
+/// The property `name` of the global `navigator`, if it is defined
+fn navigator_property(name: &str) -> Option<JsValue> {
+    navigator()
+        .and_then(|navigator| js_sys::Reflect::get(&navigator, &JsValue::from_str(name)).ok())
+        .filter(|value| !value.is_undefined())
+}
+
+/// Whether the app runs in Node, which defines `process.versions.node`
+fn is_node() -> bool {
+    static IS_NODE: OnceLock<bool> = OnceLock::new();
+    *IS_NODE.get_or_init(|| {
+        ["process", "versions", "node"]
+            .iter()
+            .try_fold(JsValue::from(js_sys::global()), |object, name| {
+                js_sys::Reflect::get(&object, &JsValue::from_str(name))
+                    .ok()
+                    .filter(|value| !value.is_undefined() && !value.is_null())
+            })
+            .is_some_and(|version| version.is_string())
+    })
+}
+
+/// The global `navigator`, which workers have too, if it is defined
+fn navigator() -> Option<JsValue> {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("navigator"))
+        .ok()
+        .filter(|navigator| navigator.is_object())
+}
+
+/// Log the hardware hints of the browser, see `Config::log_hardware_info`
+fn log_hardware_info() {
+    let mut hints = Vec::new();
+    if let Some(cores) = navigator_property("hardwareConcurrency").and_then(|v| v.as_f64()) {
+        hints.push(format!("{} logical cores", cores));
+    }
+    if let Some(memory) = navigator_property("deviceMemory").and_then(|v| v.as_f64()) {
+        hints.push(format!("{} GB of device memory", memory));
+    }
+    if !hints.is_empty() {
+        log::info!(target: "wasm_logger", "hardware: {}", hints.join(", "));
+    }
+}
+
+/// Wait until the `console` object is available, polling every 10ms, then initialize
+/// the logger which the given config, see `init`.
+///
+/// This is for bootstrap sequences which start logging before the JavaScript
+/// environment is fully set up, where the first entries would otherwise be lost.
+///
+/// ## Examples
+/// ```rust,no_run
+/// wasm_bindgen_futures::spawn_local(wasm_logger::init_when_ready(wasm_logger::Config::default()));
+/// ```
+pub async fn init_when_ready(config: Config) {
+    while !console_available() {
+        let delay = js_sys::Promise::new(&mut |resolve, _| {
+            set_timeout(&resolve, 10);
+        });
+        let _ = wasm_bindgen_futures::JsFuture::from(delay).await;
+    }
+    init(config);
+}
+
+/// Initialize the logger with the config given by the attributes of the first element
+/// which matches `selector`, e.g. `<html data-log-level="debug" data-log-prefix="my_app">`,
+/// so that logging can be changed by editing a template, without rebuilding the app:
+/// - `data-log-level`: the maximum level, such as `warn` (case-insensitive),
+/// - `data-log-prefix`: optional, see `Config::module_prefix`.
+///
+/// If the element or its `data-log-level` is missing, or a value is invalid, the default
+/// `Config` is used for that value, with a single warning listing the problems.
+///
+/// ## Examples
+/// ```rust,no_run
+/// wasm_logger::init_from_data_attr("script[data-log-level]");
+/// ```
+pub fn init_from_data_attr(selector: &str) {
+    let (config, problems) = config_from_data_attr(selector);
+    if !problems.is_empty() {
+        internal_log(
+            config.internal_log_method,
+            &format!(
+                "wasm-logger: {}; falling back to the defaults",
+                problems.join(", ")
+            ),
+        );
+    }
+    init(config);
+}
+
+/// The config of `init_from_data_attr`, with the problems of the attributes
+fn config_from_data_attr(selector: &str) -> (Config, Vec<String>) {
+    let mut config = Config::default();
+    let document = match window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => {
+            return (
+                config,
+                vec!["there is no document, e.g. in a worker".to_string()],
+            )
+        }
+    };
+    let element = match document.query_selector(selector).ok().flatten() {
+        Some(element) => element,
+        None => return (config, vec![format!("no element matches `{selector}`")]),
+    };
+    let mut problems = Vec::new();
+    match element.get_attribute("data-log-level") {
+        Some(level) => match level.trim().parse::<Level>() {
+            Ok(level) => config = Config::new(level),
+            Err(_) => problems.push(format!("`{level}` is not a log level")),
+        },
+        None => problems.push(format!("`{selector}` has no `data-log-level`")),
+    }
+    if let Some(prefix) = element.get_attribute("data-log-prefix") {
+        let prefix = prefix.trim();
+        if prefix.is_empty() {
+            problems.push("`data-log-prefix` is empty".to_string());
+        } else {
+            config = config.module_prefix(prefix);
+        }
+    }
+    (config, problems)
+}
+
+/// The window of the page, or `None` in a worker or with `Config::assume_worker`
+fn window() -> Option<web_sys::Window> {
+    if ASSUME_WORKER.load(Ordering::Relaxed) {
+        None
+    } else {
+        web_sys::window()
+    }
+}
+
+/// The function `name` of `object`, or of the global object if `None`, along with the
+/// object to call it on, if it is defined
+fn global_method(object: Option<&JsValue>, name: &str) -> Option<(JsValue, js_sys::Function)> {
+    let object = object.cloned().unwrap_or_else(|| js_sys::global().into());
+    let method = js_sys::Reflect::get(&object, &JsValue::from_str(name))
+        .ok()?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    Some((object, method))
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    window()?.local_storage().ok()?
+}
+
+/// The `localStorage` key of `Config::persist_to_local_storage`, if the logger is
+/// initialized and persisting
+fn persisted_key() -> Option<String> {
+    active_logger()?
+        .config
+        .persisted
+        .as_ref()
+        .map(|(key, _)| key.clone())
+}
+
+/// The lines stored in the `localStorage` item `key`. An item which is missing or is not a
+/// JSON array is read as empty, and the items of the array which are not strings are skipped.
+fn read_persisted(storage: &web_sys::Storage, key: &str) -> Vec<String> {
+    let item = match storage.get_item(key) {
+        Ok(Some(item)) => item,
+        _ => return Vec::new(),
+    };
+    match js_sys::JSON::parse(&item) {
+        Ok(value) if js_sys::Array::is_array(&value) => js_sys::Array::from(&value)
+            .iter()
+            .filter_map(|line| line.as_string())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Append `line` to the persisted lines of `key`, and drop the oldest ones over `max_lines`
+fn persist_line(key: &str, max_lines: usize, line: String) {
+    let storage = match local_storage() {
+        Some(storage) => storage,
+        None => return,
+    };
+    let mut lines = read_persisted(&storage, key);
+    lines.push(line);
+    let excess = lines.len().saturating_sub(max_lines);
+    lines.drain(..excess);
+    let array: js_sys::Array = lines.iter().map(|line| JsValue::from_str(line)).collect();
+    if let Ok(json) = js_sys::JSON::stringify(&array) {
+        if let Some(json) = json.as_string() {
+            let _ = storage.set_item(key, &json);
+        }
+    }
+}
+
+/// The entries kept by `Config::persist_to_local_storage`, oldest first, including those of
+/// the previous sessions which were not cleared. Empty if the logger does not persist its
+/// entries, or if the stored item is missing or corrupted.
+pub fn persisted_lines() -> Vec<String> {
+    match (persisted_key(), local_storage()) {
+        (Some(key), Some(storage)) => read_persisted(&storage, &key),
+        _ => Vec::new(),
+    }
+}
+
+/// The number of `persisted_lines`
+pub fn persisted_len() -> usize {
+    persisted_lines().len()
+}
+
+/// Remove the entries kept by `Config::persist_to_local_storage`
+pub fn clear_persisted() {
+    if let (Some(key), Some(storage)) = (persisted_key(), local_storage()) {
+        let _ = storage.remove_item(&key);
+    }
+}
+
+/// Run `f` and return the entries which it logged on the current thread, as logged but
+/// without styles, e.g. to check what an operation logs or to show it. While `f` runs,
+/// these entries are not written to the console, unless `Config::echo_captured`; the
+/// other outputs, such as `Config::remote_url`, still get them.
+///
+/// The captures are kept on a stack of the current thread, so they nest: an entry belongs
+/// to every running capture, so the outer capture also gets the entries of the inner one.
+///
+/// ## Examples
+/// ```rust,no_run
+/// wasm_logger::init(wasm_logger::Config::default());
+/// let lines = wasm_logger::capture(|| {
+///     log::info!("step 1");
+///     let inner = wasm_logger::capture(|| log::info!("step 2"));
+///     assert_eq!(inner.len(), 1);
+/// });
+/// assert_eq!(lines.len(), 2);
+/// ```
+pub fn capture(f: impl FnOnce()) -> Vec<String> {
+    /// Pops the capture when dropped, even if `f` panics
+    struct CaptureGuard;
+
+    impl Drop for CaptureGuard {
+        fn drop(&mut self) {
+            CAPTURES.with(|captures| captures.borrow_mut().pop());
+        }
+    }
+
+    CAPTURES.with(|captures| captures.borrow_mut().push(Vec::new()));
+    let guard = CaptureGuard;
+    f();
+    let lines = CAPTURES.with(|captures| {
+        captures
+            .borrow_mut()
+            .last_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    });
+    drop(guard);
+    lines
+}
+
+/// The entries kept by `Config::keep_tail`, oldest first. Empty if the logger does not keep
+/// a tail.
+pub fn tail() -> Vec<String> {
+    TAIL.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Remove the entries kept by `Config::keep_tail`
+pub fn clear_tail() {
+    TAIL.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn console_available() -> bool {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("console"))
+        .is_ok_and(|console| console.is_object())
+}
+
+/// Whether `init` has already installed `wasm-logger` as the global logger.
+///
+/// ## Examples
+/// ```rust
+/// if !wasm_logger::is_initialized() {
+///     wasm_logger::init(wasm_logger::Config::default());
+/// }
+/// assert!(wasm_logger::is_initialized());
+/// ```
+pub fn is_initialized() -> bool {
+    INITIALIZED.load(Ordering::Acquire)
+}
+
+thread_local! {
+    /// The level to restore when the active boost expires, the boosted level, and the
+    /// generation of that boost.
+    static BOOST: Cell<Option<(LevelFilter, LevelFilter, u32)>> = const { Cell::new(None) };
+
+    /// The level of `Config::level_schedule` for the current hour, if any
+    static SCHEDULED_LEVEL: Cell<Option<Level>> = const { Cell::new(None) };
+}
+
+/// Temporarily raise the maximum level to `level` for `duration_ms` milliseconds, then
+/// restore the previous maximum level.
+///
+/// Calling it again while a boost is active replaces the active boost: the new level is
+/// applied and the timer restarts, but the level restored at the end is still the one
+/// in effect before the first boost.
+///
+/// The `Config::module_level`s still apply during the boost: the level of each target is
+/// raised to `level` if it is lower, but the targets whose level is off stay off.
+pub fn boost_level(level: Level, duration_ms: u32) {
+    let (restore, generation) = BOOST.with(|boost| match boost.get() {
+        Some((restore, _, generation)) => (restore, generation.wrapping_add(1)),
+        None => (log::max_level(), 0),
+    });
+    let level = level.to_level_filter();
+    BOOST.with(|boost| boost.set(Some((restore, level, generation))));
+    log::set_max_level(restore.max(level));
+
+    let expire = Closure::once_into_js(move || {
+        BOOST.with(|boost| {
+            // A newer boost replaced this one, its own timer will do the restore.
+            if let Some((restore, _, current)) = boost.get() {
+                if current == generation {
+                    boost.set(None);
+                    if SHUT_DOWN.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    // The levels may have changed during the boost, e.g. by
+                    // `set_level_enabled`, which does not lower the boosted level
+                    let level = match active_logger() {
+                        Some(logger) if INITIALIZED.load(Ordering::Acquire) => {
+                            logger.max_level(ENABLED_LEVELS.load(Ordering::Relaxed))
+                        }
+                        _ => restore,
+                    };
+                    log::set_max_level(level);
+                }
+            }
+        })
+    });
+    set_timeout(&expire, duration_ms);
+}
+
+/// Output the entries held by `Config::defer_until_ready`, in the order in which they were
+/// logged, and output the next entries as they are logged. It does nothing if the entries
+/// are not deferred, or have already been output.
+///
+/// ## Examples
+/// ```rust,no_run
+/// wasm_logger::init(wasm_logger::Config::default().defer_until_ready(true));
+/// log::debug!("booting"); // held
+/// wasm_logger::mark_ready(); // outputs `booting`
+/// log::debug!("ready"); // output right away
+/// ```
+pub fn mark_ready() {
+    let entries = match DEFERRED.with(|deferred| deferred.borrow_mut().take()) {
+        Some(entries) => entries,
+        None => return,
+    };
+    if let Some(logger) = active_logger() {
+        for entry in &entries {
+            logger.write_console(
+                entry.level,
+                entry.method,
+                entry.group.as_deref(),
+                &entry.metadata,
+                &entry.rendered,
+            );
+        }
+    }
+}
+
+/// Flush the logger, remove the event listeners and channels it opened, and disable it for good,
+/// e.g. when a single-page app unmounts.
+///
+/// The `log` crate does not allow to remove the global logger, so the logger stays
+/// installed but every subsequent log is a no-op, and `init` can not be used again.
+pub fn shutdown() {
+    mark_ready();
+    log::logger().flush();
+    LISTENERS.with(|listeners| {
+        for listener in listeners.borrow_mut().drain(..) {
+            let _ = listener.target.remove_event_listener_with_callback(
+                listener.event,
+                listener.callback.as_ref().unchecked_ref(),
+            );
+        }
+    });
+    if let Some((channel, _)) = BROADCAST.with(|broadcast| broadcast.borrow_mut().take()) {
+        channel.close();
+    }
+    let dropped = close_websocket();
+    if let Some(logger) = active_logger() {
+        logger.report_delivery_failure(dropped);
+    }
+    if let Some(interval) = FLUSH_INTERVAL.with(|interval| interval.borrow_mut().take()) {
+        clear_interval(&interval.id);
+    }
+    close_context_groups();
+    // The timer of a pending boost restores nothing once shut down
+    BOOST.with(|boost| boost.set(None));
+    SHUT_DOWN.store(true, Ordering::Relaxed);
+    log::set_max_level(LevelFilter::Off);
+}
+
+/// Turn the entries of `level` on or off at runtime, e.g. from the checkboxes of a debug
+/// panel. It can be called before `init`. Turning a level on does not log the entries
+/// above the level of the config, but it undoes a previous call which turned it off.
+///
+/// `log::max_level` is lowered to the most verbose level which is still on, so that the
+/// levels which are off cost nothing, unless `boost_level` is active.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, LevelFilter, Log, Metadata};
+///
+/// wasm_logger::init(wasm_logger::Config::new(Level::Trace));
+/// let enabled = |level| log::logger().enabled(&Metadata::builder().level(level).build());
+///
+/// wasm_logger::set_level_enabled(Level::Debug, false);
+/// assert!(!enabled(Level::Debug));
+/// assert!(enabled(Level::Trace));
+///
+/// wasm_logger::set_level_enabled(Level::Trace, false);
+/// assert_eq!(log::max_level(), LevelFilter::Info);
+///
+/// wasm_logger::set_level_enabled(Level::Debug, true);
+/// assert!(enabled(Level::Debug));
+/// assert_eq!(log::max_level(), LevelFilter::Debug);
+/// ```
+pub fn set_level_enabled(level: Level, enabled: bool) {
+    let bit = level_bit(level);
+    let levels = if enabled {
+        ENABLED_LEVELS.fetch_or(bit, Ordering::Relaxed) | bit
+    } else {
+        ENABLED_LEVELS.fetch_and(!bit, Ordering::Relaxed) & !bit
+    };
+    if let Some(logger) = active_logger() {
+        let boosted = BOOST.with(|boost| boost.get().is_some());
+        if !boosted && !SHUT_DOWN.load(Ordering::Relaxed) {
+            log::set_max_level(logger.max_level(levels));
+        }
+    }
+}
+
+/// Silence the entries whose target is `prefix` or one of its submodules, e.g. from the
+/// devtools while a noisy module drowns out the others, until `unmute_target`. Mutes take
+/// precedence over every other rule, including `Config::always_on_target`, and last for the
+/// session only.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Log, Metadata};
+///
+/// wasm_logger::init(wasm_logger::Config::new(Level::Info));
+/// let enabled = |target| {
+///     log::logger().enabled(&Metadata::builder().level(Level::Info).target(target).build())
+/// };
+///
+/// wasm_logger::mute_target("app::poller");
+/// assert!(!enabled("app::poller::http"));
+/// assert!(enabled("app::ui"));
+///
+/// wasm_logger::unmute_target("app::poller");
+/// assert!(enabled("app::poller::http"));
+/// ```
+pub fn mute_target(prefix: &str) {
+    let mut muted = MUTED_TARGETS.write().unwrap_or_else(|e| e.into_inner());
+    if !muted.iter().any(|muted| muted == prefix) {
+        muted.push(prefix.to_string());
+    }
+}
+
+/// Undo `mute_target` for `prefix`. The submodules muted by their own call stay muted.
+pub fn unmute_target(prefix: &str) {
+    MUTED_TARGETS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|muted| muted != prefix);
+}
+
+/// Only output the entries whose target is one of `targets` or one of their submodules,
+/// e.g. to zoom in on the module which is being debugged, until `focus_clear`. Calling it
+/// again replaces the focused targets, and calling it with no targets ends the focus.
+///
+/// The focused targets bypass the target rules such as `Config::module_prefix`, but the
+/// level filters still apply to them. `mute_target` takes precedence over the focus, and
+/// the focus over `Config::always_on_target`.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Log, Metadata};
+///
+/// wasm_logger::init(wasm_logger::Config::new(Level::Info).module_prefix("app"));
+/// let enabled = |target| {
+///     log::logger().enabled(&Metadata::builder().level(Level::Info).target(target).build())
+/// };
+///
+/// wasm_logger::focus(&["app::cart", "payments"]);
+/// assert!(enabled("payments::stripe"));
+/// assert!(!enabled("app::ui"));
+///
+/// wasm_logger::focus_clear();
+/// assert!(enabled("app::ui"));
+/// assert!(!enabled("payments::stripe"));
+/// ```
+pub fn focus(targets: &[&str]) {
+    *FOCUSED_TARGETS.write().unwrap_or_else(|e| e.into_inner()) =
+        targets.iter().map(|target| target.to_string()).collect();
+}
+
+/// End the `focus`, so that the usual rules apply to every target again
+pub fn focus_clear() {
+    FOCUSED_TARGETS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+/// Run `f` in a context named `name`, e.g. the operation which it tracks, so that the
+/// entries which it logs, directly or from nested calls, can be styled with
+/// `Config::style_contextual` or grouped with `Config::group_from_context`. The contexts
+/// nest, and only apply to the current thread: an async task must enter its context again
+/// each time it is polled.
+pub fn with_context<R>(name: &str, f: impl FnOnce() -> R) -> R {
+    let _guard = enter_context(name);
+    f()
+}
+
+/// Run `f` in a `with_context` scope named `cid={id}`, so that the entries which it logs
+/// are tagged with the correlation id of a request by `Config::style_contextual` or
+/// `Config::group_from_context`. If `id` is `None`, a new one is generated with
+/// `crypto.randomUUID()`, or from `Math.random()` where `crypto` is not available.
+/// `f` is given the id, e.g. to send it in a header of the requests of the operation.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Record};
+/// use wasm_logger::Config;
+///
+/// let config = Config::default().style_contextual("color: gray");
+/// wasm_logger::with_correlation_id(Some("req-42"), |cid| {
+///     assert_eq!(cid, "req-42");
+///     let rendered = wasm_logger::render(
+///         &config,
+///         &Record::builder().level(Level::Info).args(format_args!("fetching the cart")).build(),
+///     );
+///     assert!(rendered.text.starts_with("%ccid=req-42 %cINFO"));
+/// });
+/// ```
+pub fn with_correlation_id<R>(id: Option<&str>, f: impl FnOnce(&str) -> R) -> R {
+    let id = id.map_or_else(random_uuid, str::to_string);
+    let _guard = enter_context(&format!("cid={}", id));
+    f(&id)
+}
+
+/// A random UUID v4, from `crypto.randomUUID()` if it is available
+fn random_uuid() -> String {
+    let from_crypto = || {
+        let crypto = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("crypto")).ok()?;
+        let random_uuid = js_sys::Reflect::get(&crypto, &JsValue::from_str("randomUUID"))
+            .ok()?
+            .dyn_into::<js_sys::Function>()
+            .ok()?;
+        random_uuid.call0(&crypto).ok()?.as_string()
+    };
+    from_crypto().unwrap_or_else(|| {
+        let mut bytes = [0u8; 16];
+        for byte in &mut bytes {
+            *byte = (js_sys::Math::random() * 256.0) as u8;
+        }
+        // The version and variant bits of a UUID v4
+        bytes[6] = bytes[6] & 0x0f | 0x40;
+        bytes[8] = bytes[8] & 0x3f | 0x80;
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    })
+}
+
+/// Indent the messages logged on the current thread by one more level, of
+/// `Config::indent_width` spaces, e.g. to show the depth of a recursive function, until
+/// `indent_pop`. Prefer `indent`, which also pops when the scope ends.
+pub fn indent_push() {
+    INDENT_DEPTH.with(|depth| depth.set(depth.get() + 1));
+}
+
+/// Undo an `indent_push`. It does nothing if the messages are not indented.
+pub fn indent_pop() {
+    INDENT_DEPTH.with(|depth| depth.set(depth.get().saturating_sub(1)));
+}
+
+/// Indent the messages logged on the current thread by one more level until the returned
+/// guard is dropped, see `indent_push`.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Record};
+///
+/// use wasm_logger::Config;
+///
+/// fn visit(config: &Config, depth: u32, lines: &mut Vec<String>) {
+///     let rendered = wasm_logger::render(
+///         config,
+///         &Record::builder()
+///             .args(format_args!("depth {}", depth))
+///             .level(Level::Info)
+///             .target("app")
+///             .build(),
+///     );
+///     lines.push(rendered.plain_text());
+///     if depth < 2 {
+///         let _indent = wasm_logger::indent();
+///         visit(config, depth + 1, lines);
+///     }
+/// }
+///
+/// let mut lines = Vec::new();
+/// visit(&Config::new(Level::Info).indent_width(4), 0, &mut lines);
+/// assert_eq!(
+///     lines,
+///     [
+///         "INFO app:[Unknown] depth 0",
+///         "INFO app:[Unknown]     depth 1",
+///         "INFO app:[Unknown]         depth 2",
+///     ]
+/// );
+/// ```
+pub fn indent() -> IndentGuard {
+    indent_push();
+    IndentGuard(())
+}
+
+/// A level of `indent`, which is popped when dropped
+#[must_use = "the indentation ends when the guard is dropped"]
+#[derive(Debug)]
+pub struct IndentGuard(());
+
+impl Drop for IndentGuard {
+    fn drop(&mut self) {
+        indent_pop();
+    }
+}
+
+/// The runtime settings of the logger, taken by `snapshot_state` and put back by
+/// `restore_state`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoggerState {
+    max_level: LevelFilter,
+    enabled_levels: u8,
+    muted_targets: Vec<String>,
+    focused_targets: Vec<String>,
+    boost: Option<(LevelFilter, LevelFilter, u32)>,
+}
+
+/// Take the settings which can be changed at runtime: the maximum level, the levels of
+/// `set_level_enabled`, the targets of `mute_target` and `focus`, and the `boost_level`
+/// in progress. `restore_state` puts them back, e.g. after a test which changes them, or
+/// for a "reset to defaults" button.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Log, Metadata};
+///
+/// wasm_logger::init(wasm_logger::Config::new(Level::Info));
+/// let enabled = |level, target| {
+///     log::logger().enabled(&Metadata::builder().level(level).target(target).build())
+/// };
+/// let defaults = wasm_logger::snapshot_state();
+///
+/// wasm_logger::mute_target("app::poller");
+/// wasm_logger::set_level_enabled(Level::Info, false);
+/// assert!(!enabled(Level::Info, "app"));
+///
+/// wasm_logger::restore_state(defaults);
+/// assert!(enabled(Level::Info, "app"));
+/// assert!(enabled(Level::Info, "app::poller"));
+/// ```
+pub fn snapshot_state() -> LoggerState {
+    LoggerState {
+        max_level: log::max_level(),
+        enabled_levels: ENABLED_LEVELS.load(Ordering::Relaxed),
+        muted_targets: MUTED_TARGETS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone(),
+        focused_targets: FOCUSED_TARGETS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone(),
+        boost: BOOST.with(Cell::get),
+    }
+}
+
+/// Put back the runtime settings taken by `snapshot_state`. A `boost_level` which was in
+/// progress is only restored if it is still running; otherwise the level which it
+/// raised is restored. This does not undo `shutdown`.
+pub fn restore_state(state: LoggerState) {
+    ENABLED_LEVELS.store(state.enabled_levels, Ordering::Relaxed);
+    *MUTED_TARGETS.write().unwrap_or_else(|e| e.into_inner()) = state.muted_targets;
+    *FOCUSED_TARGETS.write().unwrap_or_else(|e| e.into_inner()) = state.focused_targets;
+    let running = BOOST.with(Cell::get).map(|(_, _, generation)| generation);
+    let (boost, max_level) = match state.boost {
+        Some((restore, _, generation)) if running != Some(generation) => (None, restore),
+        boost => (boost, state.max_level),
+    };
+    BOOST.with(|cell| cell.set(boost));
+    if !SHUT_DOWN.load(Ordering::Relaxed) {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Whether `target` is focused by `focus`, or `None` if focus is not active
+fn focused(target: &str) -> Option<bool> {
+    let focused = FOCUSED_TARGETS.read().unwrap_or_else(|e| e.into_inner());
+    if focused.is_empty() {
+        None
+    } else {
+        Some(
+            focused
+                .iter()
+                .any(|prefix| matches_module_prefix(target, prefix)),
+        )
+    }
+}
+
+/// Whether `target` is muted by `mute_target`
+fn muted(target: &str) -> bool {
+    MUTED_TARGETS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|prefix| matches_module_prefix(target, prefix))
+}
+
+/// The bit of `level` in `ENABLED_LEVELS`
+fn level_bit(level: Level) -> u8 {
+    1 << (level as usize - 1)
+}
+
+/// The most verbose level up to `max_level` which is on in `levels`
+fn enabled_max_level(max_level: LevelFilter, levels: u8) -> LevelFilter {
+    LevelFilter::iter()
+        .filter(|filter| *filter <= max_level)
+        .filter(|filter| filter.to_level().is_none_or(|l| levels & level_bit(l) != 0))
+        .max()
+        .unwrap_or(LevelFilter::Off)
+}
+
+/// Log `bytes` as a classic hex dump: an offset column, 16 bytes in hex per row, and
+/// their ASCII representation (`.` for non-printable bytes). Logged with the file and line
+/// of the caller and the target `wasm_logger`.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::hexdump(log::Level::Debug, "handshake", &[0x16, 0x03, 0x01, 0x02, 0x00]);
+/// ```
+/// logs:
+/// ```text
+/// handshake (5 bytes)
+/// 00000000  16 03 01 02 00                                    |.....|
+/// ```
+#[track_caller]
+pub fn hexdump(level: Level, label: &str, bytes: &[u8]) {
+    log_at(
+        level,
+        std::panic::Location::caller(),
+        format_args!("{} ({} bytes)\n{}", label, bytes.len(), hex_dump(bytes)),
+    );
+}
+
+fn hex_dump(bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        if row > 0 {
+            dump.push('\n');
+        }
+        dump.push_str(&format!("{:08x} ", row * 16));
+        for i in 0..16 {
+            if i == 8 {
+                dump.push(' ');
+            }
+            match chunk.get(i) {
+                Some(byte) => dump.push_str(&format!(" {:02x}", byte)),
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str("  |");
+        dump.extend(chunk.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push('|');
+    }
+    dump
+}
+
+thread_local! {
+    /// The keys already used by `log_once`
+    static LOGGED_ONCE: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+
+    /// The last value given to `log_on_change` for each key
+    static LAST_VALUES: RefCell<HashMap<String, Box<dyn std::any::Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Log `message` only the first time this function is called with `key`, e.g. for a
+/// deprecation notice in a render loop. Later calls with the same `key` do nothing
+/// for the rest of the session, even with a different message. Logged with the file and
+/// line of the caller and the target `wasm_logger`.
+///
+/// ## Examples
+/// ```rust
+/// for _ in 0..60 {
+///     // Logged only once
+///     wasm_logger::log_once(log::Level::Warn, "old-api", "`old_api` is deprecated");
+/// }
+/// ```
+///
+/// The location is the one of the call, as with the `log` macros:
+/// ```rust
+/// use std::sync::Mutex;
+///
+/// use log::{Log, Metadata, Record};
+///
+/// static LOCATION: Mutex<Option<(String, u32)>> = Mutex::new(None);
+///
+/// struct Spy;
+///
+/// impl Log for Spy {
+///     fn enabled(&self, _: &Metadata<'_>) -> bool {
+///         true
+///     }
+///
+///     fn log(&self, record: &Record<'_>) {
+///         let location = (record.file().unwrap().to_string(), record.line().unwrap());
+///         *LOCATION.lock().unwrap() = Some(location);
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// log::set_logger(&Spy).unwrap();
+/// log::set_max_level(log::LevelFilter::Trace);
+/// wasm_logger::log_once(log::Level::Info, "tip", "press ? for help"); let line = line!();
+/// assert_eq!(*LOCATION.lock().unwrap(), Some((file!().to_string(), line)));
+/// ```
+#[track_caller]
+pub fn log_once(level: Level, key: &str, message: &str) {
+    let first = LOGGED_ONCE.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        !keys.contains(key) && keys.insert(key.to_string())
+    });
+    if first {
+        log_at(
+            level,
+            std::panic::Location::caller(),
+            format_args!("{}", message),
+        );
+    } else {
+        SUPPRESSED_DEDUPED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Count one more frame for `Config::show_frame`, e.g. at the start of each
+/// `requestAnimationFrame` callback, and return its number. The count starts at 0.
+pub fn next_frame() -> u64 {
+    FRAME.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Log `{key}: {old} -> {new}` when `value` differs from the previous value given for
+/// `key`, e.g. to log the state of a connection from a polling loop only when it changes.
+/// Unlike `Config::dedup_consecutive`, nothing is logged while the value stays the same,
+/// nor for the first value of a key, which is only stored. A value of another type than
+/// the previous one is a change, logged as `{key}: {new}`. The entry is logged as by the
+/// other [helpers](crate#helpers). Return whether the value changed.
+///
+/// ## Examples
+/// ```rust
+/// use log::Level;
+///
+/// for status in ["connecting", "connecting", "online", "online"] {
+///     wasm_logger::log_on_change("socket", status, Level::Info);
+/// } // logs `socket: connecting -> online` once
+/// assert!(!wasm_logger::log_on_change("socket", "online", Level::Info));
+/// assert!(wasm_logger::log_on_change("socket", "offline", Level::Info));
+/// ```
+#[track_caller]
+pub fn log_on_change<T: PartialEq + std::fmt::Display + 'static>(
+    key: &str,
+    value: T,
+    level: Level,
+) -> bool {
+    // Logged once the map is released, in case `Display` calls `log_on_change` too
+    let change = LAST_VALUES.with(|values| {
+        let mut values = values.borrow_mut();
+        let previous = match values.get_mut(key) {
+            Some(previous) => previous,
+            None => {
+                values.insert(key.to_string(), Box::new(value));
+                return None;
+            }
+        };
+        let change = match previous.downcast_ref::<T>() {
+            Some(previous_value) if *previous_value == value => return None,
+            Some(previous_value) => format!("{}: {} -> {}", key, previous_value, value),
+            None => format!("{}: {}", key, value),
+        };
+        *previous = Box::new(value);
+        Some(change)
+    });
+    match change {
+        Some(change) => {
+            log_at(
+                level,
+                std::panic::Location::caller(),
+                format_args!("{}", change),
+            );
+            true
+        }
+        None => false,
+    }
+}
+
+/// Normalize `message` into its template, as compared by `Config::dedup_normalized`: the
+/// UUIDs are replaced with `<uuid>` and the runs of digits with `#`.
+///
+/// ## Examples
+/// ```rust
+/// use wasm_logger::normalize_message;
+///
+/// assert_eq!(normalize_message("user 1 connected"), "user # connected");
+/// assert_eq!(
+///     normalize_message("user 1 connected"),
+///     normalize_message("user 20 connected")
+/// );
+/// assert_eq!(normalize_message("took 1.25s, 3 retries"), "took #.#s, # retries");
+/// assert_eq!(
+///     normalize_message("session 6f1c2e7a-6d0b-4c5e-9a3b-2f1e0d9c8b7a expired"),
+///     "session <uuid> expired"
+/// );
+/// assert_eq!(normalize_message("no number"), "no number");
+/// ```
+pub fn normalize_message(message: &str) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut normalized = String::with_capacity(message.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if is_uuid_at(&chars, i) {
+            normalized.push_str("<uuid>");
+            i += 36;
+        } else if chars[i].is_ascii_digit() {
+            normalized.push('#');
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+        } else {
+            normalized.push(chars[i]);
+            i += 1;
+        }
+    }
+    normalized
+}
+
+/// Whether a UUID, 32 hex digits in groups of 8, 4, 4, 4 and 12 separated by `-`, starts
+/// at `start` in `chars` and is not part of a longer word
+fn is_uuid_at(chars: &[char], start: usize) -> bool {
+    let end = start + 36;
+    if end > chars.len()
+        || start > 0 && chars[start - 1].is_ascii_alphanumeric()
+        || chars.get(end).is_some_and(|c| c.is_ascii_alphanumeric())
+    {
+        return false;
+    }
+    chars[start..end].iter().enumerate().all(|(i, c)| match i {
+        8 | 13 | 18 | 23 => *c == '-',
+        _ => c.is_ascii_hexdigit(),
+    })
+}
+
+/// The numbers of entries which were dropped, by reason, see `suppression_stats`
+static SUPPRESSED_DEDUPED: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED_RATE_LIMITED: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED_FILTERED: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED_BUFFER_OVERFLOW: AtomicU64 = AtomicU64::new(0);
+static SUPPRESSED_OVER_LIMIT: AtomicU64 = AtomicU64::new(0);
+
+/// The number of entries counted by `Config::max_total_logs` since `resume_logging`
+static TOTAL_LOGGED: AtomicU64 = AtomicU64::new(0);
+
+/// The numbers of entries dropped by each mechanism since the start of the session,
+/// or the last `reset_suppression_stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SuppressionStats {
+    /// The repeated calls of `log_once`, and the repeats collapsed by
+    /// `Config::dedup_consecutive`
+    pub deduped: u64,
+    /// The entries over `Config::max_logs_per_second`
+    pub rate_limited: u64,
+    /// The entries which reached the logger but were rejected by its filters: the target
+    /// rules, the module levels, `set_level_enabled` or `shutdown`. The entries above
+    /// `log::max_level` are rejected by the `log` macros before they reach the logger, so
+    /// they are not counted.
+    pub filtered: u64,
+    /// The oldest entries dropped from the outputs which buffer them, over
+    /// `Config::max_buffered_logs`
+    pub buffer_overflow: u64,
+    /// The entries over `Config::max_total_logs`
+    pub over_limit: u64,
+}
+
+/// Count the entries which were dropped, e.g. to show in a debug overlay that the
+/// filters do not hide too much.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::init(wasm_logger::Config::new(log::Level::Info).module_prefix("app"));
+/// log::info!(target: "other", "filtered out");
+/// assert_eq!(wasm_logger::suppression_stats().filtered, 1);
+///
+/// wasm_logger::reset_suppression_stats();
+/// assert_eq!(wasm_logger::suppression_stats(), Default::default());
+/// ```
+pub fn suppression_stats() -> SuppressionStats {
+    SuppressionStats {
+        deduped: SUPPRESSED_DEDUPED.load(Ordering::Relaxed),
+        rate_limited: SUPPRESSED_RATE_LIMITED.load(Ordering::Relaxed),
+        filtered: SUPPRESSED_FILTERED.load(Ordering::Relaxed),
+        buffer_overflow: SUPPRESSED_BUFFER_OVERFLOW.load(Ordering::Relaxed),
+        over_limit: SUPPRESSED_OVER_LIMIT.load(Ordering::Relaxed),
+    }
+}
+
+/// Set the counts of `suppression_stats` back to zero
+pub fn reset_suppression_stats() {
+    for counter in [
+        &SUPPRESSED_DEDUPED,
+        &SUPPRESSED_RATE_LIMITED,
+        &SUPPRESSED_FILTERED,
+        &SUPPRESSED_BUFFER_OVERFLOW,
+        &SUPPRESSED_OVER_LIMIT,
+    ] {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Log again after `Config::max_total_logs` paused the logging, with a new budget of
+/// entries, e.g. from a button of a debug overlay once the runaway loop is fixed.
+pub fn resume_logging() {
+    TOTAL_LOGGED.store(0, Ordering::Relaxed);
+}
+
+/// Return the value of `result`, or log its error at the error level as `{context}: {err}`
+/// and return `None`, for the operations whose failure is logged and then ignored. The
+/// entry is logged as by the other [helpers](crate#helpers).
+///
+/// ## Examples
+/// ```rust
+/// let port: Option<u16> = wasm_logger::log_err("80a".parse::<u16>(), "invalid port");
+/// assert_eq!(port, None);
+/// ```
+#[track_caller]
+pub fn log_err<T, E: std::fmt::Display>(result: Result<T, E>, context: &str) -> Option<T> {
+    log_err_with(Level::Error, result, context)
+}
+
+/// Like `log_err`, but log the error at `level`
+#[track_caller]
+pub fn log_err_with<T, E: std::fmt::Display>(
+    level: Level,
+    result: Result<T, E>,
+    context: &str,
+) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log_at(
+                level,
+                std::panic::Location::caller(),
+                format_args!("{}: {}", context, err),
+            );
+            None
+        }
+    }
+}
+
+/// Log the JavaScript error `err`, e.g. the `Err` of a `wasm_bindgen` call, at the error
+/// level as `{context}: {err}`, where `{err}` is readable rather than `[object Object]`:
+/// - a JS `Error` shows its name and message, followed by its stack trace, without the
+///   frames of `Config::trim_stack_frames`,
+/// - a string is shown as is, and another value as its JSON, if it has one.
+///
+/// The entry is logged as by the other [helpers](crate#helpers).
+///
+/// ## Examples
+/// ```rust,no_run
+/// # fn fetch_user() -> Result<(), wasm_bindgen::JsValue> { Ok(()) }
+/// if let Err(err) = fetch_user() {
+///     wasm_logger::log_js_error("could not fetch the user", &err);
+/// }
+/// ```
+#[track_caller]
+pub fn log_js_error(context: &str, err: &JsValue) {
+    let caller = std::panic::Location::caller();
+    // Skip the JS calls if the entry is filtered out anyway
+    if Level::Error > log::STATIC_MAX_LEVEL || Level::Error > log::max_level() {
+        return;
+    }
+    let text = match err.dyn_ref::<js_sys::Error>() {
+        Some(error) => {
+            let header = String::from(error.to_string());
+            let logger = active_logger();
+            let trim_patterns = logger
+                .as_ref()
+                .map_or(&[][..], |logger| &logger.config.trim_stack_frames[..]);
+            match error_stack(err, &header, trim_patterns).filter(|stack| !stack.is_empty()) {
+                Some(stack) => format!("{}\n{}", header, stack),
+                None => header,
+            }
+        }
+        None => err
+            .as_string()
+            .or_else(|| {
+                js_sys::JSON::stringify(err)
+                    .ok()
+                    .and_then(|json| json.as_string())
+            })
+            .unwrap_or_else(|| format!("{:?}", err)),
+    };
+    log_at(Level::Error, caller, format_args!("{}: {}", context, text));
+}
+
+/// Return whether `left` and `right` are equal, and if they are not, log at the warn level
+/// like a failed `assert_eq!`, without panicking, which would abort the whole wasm module:
+/// ```text
+/// {label}: left != right
+///   left:  {left:?}
+///   right: {right:?}
+/// ```
+/// The entry is logged as by the other [helpers](crate#helpers), and its continuation
+/// lines follow `Config::indent_continuation`.
+///
+/// ## Examples
+/// ```rust
+/// assert!(wasm_logger::check_eq(&[1, 2], &[1, 2], "sorted ids"));
+/// assert!(!wasm_logger::check_eq(&"cart", &"card", "route"));
+/// ```
+#[track_caller]
+pub fn check_eq<T: std::fmt::Debug + PartialEq>(left: &T, right: &T, label: &str) -> bool {
+    let equal = left == right;
+    if !equal {
+        log_at(
+            Level::Warn,
+            std::panic::Location::caller(),
+            format_args!(
+                "{}: left != right\n  left:  {:?}\n  right: {:?}",
+                label, left, right
+            ),
+        );
+    }
+    equal
+}
+
+/// Log `err` at the error level, followed by its whole `source()` chain, one indented
+/// `caused by:` line per level:
+/// ```text
+/// failed to load the settings
+///   caused by: failed to read `settings.json`
+///     caused by: file not found
+/// ```
+#[track_caller]
+pub fn error_chain(err: &dyn std::error::Error) {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    let mut depth = 1;
+    while let Some(err) = source {
+        message.push_str(&format!("\n{}caused by: {}", "  ".repeat(depth), err));
+        source = err.source();
+        depth += 1;
+    }
+    log_at(
+        Level::Error,
+        std::panic::Location::caller(),
+        format_args!("{}", message),
+    );
+}
+
+/// Log a message with the formatting, styling and outputs configured by `init`, without
+/// going through the `log` macros, e.g. for messages which come from JavaScript or from
+/// a crate which does not use `log`. The message is still subject to the level and
+/// target filters.
+///
+/// The message goes through the active logger: the one of `init`, or the config picked by
+/// `use_config` among those of `register_config`. So `emit` does nothing until `init` or
+/// `use_config` has run. To render a record with a config which is not installed, use
+/// `render`.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::emit(
+///     wasm_logger::Level::Info,
+///     "js::bridge",
+///     Some("app.js"),
+///     Some(12),
+///     format_args!("{} items loaded", 3),
+/// );
+/// ```
+pub fn emit(
+    level: Level,
+    target: &str,
+    file: Option<&str>,
+    line: Option<u32>,
+    args: std::fmt::Arguments<'_>,
+) {
+    if let Some(logger) = active_logger() {
+        if level <= log::STATIC_MAX_LEVEL && level <= log::max_level() {
+            logger.log(
+                &Record::builder()
+                    .level(level)
+                    .target(target)
+                    .file(file)
+                    .line(line)
+                    .args(args)
+                    .build(),
+            );
+        }
+    }
+}
+
+/// Log `args` at `level`, a value known at runtime, e.g. picked from an HTTP status, with
+/// `target` and the file and line of the caller. It goes through the whole logger,
+/// including its filters, like the `log` macros.
+///
+/// ## Examples
+/// ```rust
+/// use wasm_logger::Level;
+///
+/// let status = 503;
+/// let level = match status {
+///     500.. => Level::Error,
+///     400.. => Level::Warn,
+///     _ => Level::Info,
+/// };
+/// wasm_logger::log_dyn(level, "app::http", format_args!("GET /cart: {}", status));
+/// ```
+#[track_caller]
+pub fn log_dyn(level: Level, target: &str, args: std::fmt::Arguments<'_>) {
+    log_at_target(level, target, std::panic::Location::caller(), args);
+}
+
+/// Render `record` as the logger configured by `config` would, without logging it, e.g. to
+/// write it to a custom output or to test a config. The level colors are the default ones,
+/// regardless of `Config::adapt_to_color_scheme`.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Record};
+///
+/// let config = wasm_logger::Config::new(Level::Info);
+/// let record = Record::builder()
+///     .args(format_args!("hello"))
+///     .level(Level::Info)
+///     .file(Some("src/main.rs"))
+///     .line(Some(3))
+///     .build();
+/// let rendered = wasm_logger::render(&config, &record);
+/// assert_eq!(rendered.text, "%cINFO%c src/main.rs:3%c hello");
+/// assert_eq!(rendered.styles.len(), 3);
+/// assert_eq!(rendered.plain_text(), "INFO src/main.rs:3 hello");
+///
+/// // The CSS after the `#` of the target styles the message, see the README
+/// let record = Record::builder()
+///     .args(format_args!("special"))
+///     .level(Level::Info)
+///     .target("app#color: purple")
+///     .build();
+/// let rendered = wasm_logger::render(&config, &record);
+/// assert_eq!(rendered.plain_text(), "INFO app:[Unknown] special");
+/// assert_eq!(rendered.styles.last().unwrap(), "color: purple");
+/// ```
+pub fn render(config: &Config, record: &Record<'_>) -> RenderedLog {
+    let style = Style::new(config, ColorScheme::Any);
+    match split_target_style(record.target()) {
+        Some((target, Some(css))) => config.render(
+            &style.with_message_style(css),
+            &with_target(record, &target),
+        ),
+        Some((target, None)) => config.render(&style, &with_target(record, &target)),
+        None => config.render(&style, record),
+    }
+}
+
+/// The strings which the logger configured by `config` would pass to the console method
+/// for `record`: the text with its `%c` directives, then the styles in order, to test the
+/// exact output of a config without a browser. It is empty if the entry would be dropped
+/// by `Config::empty_message` or by `Config::pre_emit`. Like `render`, it does not apply
+/// the level and target filters, and uses the default level colors.
+///
+/// ## Examples
+/// ```rust
+/// use log::{Level, Record};
+///
+/// let config = wasm_logger::Config::new(Level::Info).message_on_new_line();
+/// let record = Record::builder()
+///     .args(format_args!("hello"))
+///     .level(Level::Warn)
+///     .file(Some("src/main.rs"))
+///     .line(Some(3))
+///     .build();
+/// let args = wasm_logger::test_capture_console_args(&config, &record);
+/// assert_eq!(args[0], "%cWARN%c src/main.rs:3%c\nhello");
+/// assert_eq!(args.len(), 4);
+/// assert!(args[1].contains("background: orange"));
+/// ```
+pub fn test_capture_console_args(config: &Config, record: &Record<'_>) -> Vec<String> {
+    if config.skips(record) {
+        return Vec::new();
+    }
+    config
+        .apply_pre_emit(record.level(), render(config, record))
+        .map_or_else(Vec::new, |rendered| rendered.args().cloned().collect())
+}
+
+/// Describe the effective settings of `config`, one setting per line, e.g. to check
+/// once at startup that the logger is set up as intended.
+///
+/// ## Examples
+/// ```rust
+/// let config = wasm_logger::Config::new(log::Level::Info).module_prefix("app");
+/// let description = wasm_logger::describe(&config);
+/// assert!(description.contains("level: INFO"));
+/// assert!(description.contains("module prefix: app"));
+/// ```
+pub fn describe(config: &Config) -> String {
+    let or_none = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+    let list = |values: &[String]| {
+        if values.is_empty() {
+            "none".to_string()
+        } else {
+            values.join(", ")
+        }
+    };
+    #[cfg_attr(not(any(feature = "kv", feature = "regex")), allow(unused_mut))]
+    let mut lines = vec![
+        format!("level: {}", config.level),
+        format!(
+            "level schedule: {}",
+            list(
+                &config
+                    .level_schedule
+                    .iter()
+                    .map(|(start, end, level)| format!("{}h-{}h={}", start, end, level))
+                    .collect::<Vec<_>>()
+            )
+        ),
+        format!("module prefix: {}", or_none(config.module_prefix.clone())),
+        format!("exact targets: {}", list(&config.exact_targets)),
+        format!("always-on targets: {}", list(&config.always_on_targets)),
+        format!(
+            "module levels: {}",
+            list(
+                &config
+                    .module_levels
+                    .iter()
+                    .map(|(target, level)| format!("{}={}", target, level))
+                    .collect::<Vec<_>>()
+            )
+        ),
+        format!("output format: {:?}", config.output_format),
+        format!("schema version: {}", config.schema_version),
+        format!(
+            "formatter: {}",
+            if config.formatter.is_some() {
+                "custom"
+            } else {
+                "built-in"
+            }
+        ),
+        format!("dynamic hook: {}", config.dynamic.is_some()),
+        format!("pre emit hook: {}", config.pre_emit.is_some()),
+        format!("sinks: {}", config.sinks.len()),
+        format!("on record hook: {}", config.on_record.is_some()),
+        format!(
+            "delivery failure hook: {}",
+            config.on_delivery_failure.is_some()
+        ),
+        format!(
+            "timestamp format: {}",
+            or_none(config.timestamp_format.as_ref().map(|f| format!("{:?}", f)))
+        ),
+        format!("performance precision: {}", config.performance_precision),
+        format!(
+            "timestamp levels: {}",
+            config.timestamp_levels.as_ref().map_or_else(
+                || "all".to_string(),
+                |levels| levels
+                    .iter()
+                    .map(Level::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        ),
+        format!(
+            "elide repeated timestamps: {}",
+            config.elide_repeated_timestamps
+        ),
+        format!("message location: {:?}", config.message_location),
+        format!("newline indent: {:?}", config.newline_indent),
+        format!("message prefix: {:?}", config.message_prefix),
+        format!("indent width: {}", config.indent_width),
+        format!("message suffix: {:?}", config.message_suffix),
+        format!(
+            "global fields: {}",
+            list(
+                &config
+                    .global_fields
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, value))
+                    .collect::<Vec<_>>()
+            )
+        ),
+        format!("show global fields: {}", config.show_global_fields),
+        format!(
+            "kv format: {:?} between pairs, {:?} between key and value",
+            config.kv_pair_separator, config.kv_separator
+        ),
+        format!(
+            "max token length: {}",
+            or_none(config.max_token_len.map(|max_len| max_len.to_string()))
+        ),
+        format!("sanitize control chars: {}", config.sanitize_control_chars),
+        format!(
+            "max message length: {}",
+            or_none(config.max_message_len.map(|max_len| max_len.to_string()))
+        ),
+        format!("truncate mode: {:?}", config.truncate_mode),
+        format!("truncation indicator: {:?}", config.truncation_indicator),
+        format!("level display: {:?}", config.level_display),
+        format!("level case: {:?}", config.level_case),
+        format!(
+            "level emoji prefix: {}",
+            if config.level_emoji_prefix {
+                config.level_emojis.join(" ")
+            } else {
+                "none".to_string()
+            }
+        ),
+        format!("style mode: {:?}", config.style_mode),
+        format!("ansi colors: {}", config.ansi_colors),
+        format!("badge opacity: {}", config.badge_opacity),
+        format!(
+            "severity gradient: {}",
+            or_none(
+                config
+                    .severity_gradient
+                    .map(|shift| format!("hue shift of {} degrees", shift))
+            )
+        ),
+        format!("adapt to color scheme: {}", config.adapt_to_color_scheme),
+        format!("color by target: {}", config.color_by_target),
+        format!("instance tag: {}", or_none(config.instance_tag.clone())),
+        format!("show frame: {}", config.show_frame),
+        format!(
+            "separator: {:?} x {} at {}",
+            config.separator_char, config.separator_width, config.separator_level
+        ),
+        format!(
+            "target colors: {}",
+            list(
+                &config
+                    .target_colors
+                    .iter()
+                    .map(|(prefix, css)| format!("{} => {}", prefix, css))
+                    .collect::<Vec<_>>()
+            )
+        ),
+        format!("max style segments: {}", config.max_style_segments),
+        format!("location fallback: {:?}", config.location_fallback),
+        format!(
+            "empty target placeholder: {}",
+            config.empty_target_placeholder
+        ),
+        format!("empty message: {:?}", config.empty_message),
+        format!(
+            "stripped path prefixes: {}",
+            list(&config.strip_path_prefixes)
+        ),
+        format!("indent continuation: {}", config.indent_continuation),
+        format!(
+            "thousands separator: {}",
+            or_none(config.thousands_separator.map(|c| format!("{:?}", c)))
+        ),
+        format!("si units: {}", config.si_units),
+        format!(
+            "dedup consecutive: {}",
+            if config.dedup_normalized {
+                "normalized"
+            } else if config.dedup_consecutive {
+                "exact"
+            } else {
+                "no"
+            }
+        ),
+        format!(
+            "max logs per second: {}",
+            or_none(config.max_logs_per_second.map(|max| max.to_string()))
+        ),
+        format!(
+            "max total logs: {}",
+            or_none(config.max_total_logs.map(|max| max.to_string()))
+        ),
+        format!(
+            "debug uses console.debug: {}",
+            config.debug_uses_console_debug
+        ),
+        format!("quiet info: {}", config.quiet_info),
+        format!("trace with stack: {}", config.trace_with_stack),
+        format!("unix streams: {}", config.unix_streams),
+        format!(
+            "contextual style: {}",
+            or_none(config.contextual_style.clone())
+        ),
+        format!("group from context: {}", config.group_from_context),
+        format!(
+            "severity overrides: {}",
+            list(
+                &config
+                    .severity_overrides
+                    .iter()
+                    .map(|(level, severity)| format!("{}={:?}", level, severity))
+                    .collect::<Vec<_>>()
+            )
+        ),
+        format!("performance markers: {}", config.performance_markers),
+        format!(
+            "warn if slow: {}",
+            or_none(config.slow_log_micros.map(|micros| format!("{}µs", micros)))
+        ),
+        format!("rich grouping: {}", config.rich_grouping),
+        format!("auto group by target: {}", config.auto_group_by_target),
+        format!(
+            "defer until ready: {}",
+            if config.defer_until_ready {
+                format!("yes, at most {}ms", config.ready_timeout_ms)
+            } else {
+                "no".to_string()
+            }
+        ),
+        format!("log hardware info: {}", config.log_hardware_info),
+        format!(
+            "log environment on init: {}",
+            config.log_environment_on_init
+        ),
+        format!("stack trace on error: {}", config.stack_trace_on_error),
+        format!("trimmed stack frames: {}", list(&config.trim_stack_frames)),
+        format!(
+            "internal log method: {}",
+            or_none(config.internal_log_method.map(|m| format!("{:?}", m)))
+        ),
+        format!(
+            "broadcast channel: {}",
+            or_none(config.broadcast_channel.clone())
+        ),
+        format!("remote url: {}", or_none(config.remote_url.clone())),
+        format!("max buffered logs: {}", config.max_buffered_logs),
+        format!(
+            "websocket sink: {}",
+            or_none(
+                config
+                    .websocket_sink
+                    .as_ref()
+                    .map(|(url, min_level)| format!("{} from {}", url, min_level))
+            )
+        ),
+        format!(
+            "breadcrumbs: {}",
+            or_none(config.breadcrumbs.as_ref().map(|(global, min_level)| {
+                format!("{}.{} from {}", global, config.breadcrumb_method, min_level)
+            }))
+        ),
+        format!("plain capture: {}", config.plain_capture.is_some()),
+        format!("tail: {} entries", config.tail_len),
+        format!("echo captured: {}", config.echo_captured),
+        format!(
+            "persisted to local storage: {}",
+            or_none(
+                config
+                    .persisted
+                    .as_ref()
+                    .map(|(key, max_lines)| format!("{} (last {} lines)", key, max_lines))
+            )
+        ),
+        format!(
+            "dom events: {}",
+            or_none(
+                config
+                    .dom_events
+                    .as_ref()
+                    .map(|(selector, name)| format!("{} on {}", name, selector))
+            )
+        ),
+        format!("use sendBeacon: {}", config.use_send_beacon),
+        format!("assume worker: {}", config.assume_worker),
+        format!(
+            "flush interval: {}",
+            or_none(
+                config
+                    .flush_interval_ms
+                    .map(|millis| format!("{} ms", millis))
+            )
+        ),
+    ];
+    #[cfg(feature = "kv")]
+    lines.push(format!(
+        "timestamp from kv: {}",
+        or_none(config.timestamp_kv.clone())
+    ));
+    #[cfg(feature = "kv")]
+    lines.push(format!("kv max depth: {}", config.kv_max_depth));
+    #[cfg(feature = "kv")]
+    lines.push(format!(
+        "kv value max len: {}",
+        or_none(config.kv_value_max_len.map(|len| len.to_string()))
+    ));
+    #[cfg(feature = "regex")]
+    lines.push(format!(
+        "highlights: {}",
+        list(
+            &config
+                .highlights
+                .iter()
+                .map(|(regex, css)| format!("/{}/ => {}", regex, css))
+                .collect::<Vec<_>>()
+        )
+    ));
+    #[cfg(feature = "regex")]
+    lines.push(format!(
+        "target regexes: {}",
+        list(
+            &config
+                .target_regexes
+                .iter()
+                .map(|(regex, level)| format!("/{}/ => {}", regex, level))
+                .collect::<Vec<_>>()
+        )
+    ));
+    lines.join("\n")
+}
+
+/// Log `message` with a green `✓ SUCCESS` badge through `console.info`, e.g. for a
+/// completed operation. This is not a `log` level: it is not subject to the level and
+/// target filters, only to `shutdown`.
+pub fn success(message: &str) {
+    log_badge(
+        ConsoleMethod::Info,
+        "✓ SUCCESS",
+        "color: white; padding: 0 3px; background: #2e7d32;",
+        message,
+    );
+}
+
+/// Log `message` with a purple `NOTICE` badge through `console.log`. Like `success`,
+/// this is not a `log` level.
+pub fn notice(message: &str) {
+    log_badge(
+        ConsoleMethod::Log,
+        "NOTICE",
+        "color: white; padding: 0 3px; background: rebeccapurple;",
+        message,
+    );
+}
+
+/// Increment and log the counter of `label` with `console.count`, e.g. to see how many
+/// times a code path runs. Like `success`, this is not subject to the level and target
+/// filters, only to `shutdown`.
+pub fn count(label: &str) {
+    if !SHUT_DOWN.load(Ordering::Relaxed) {
+        console::count_with_label(label);
+    }
+}
+
+/// Reset the counter of `label` of `count` with `console.countReset`
+pub fn count_reset(label: &str) {
+    if !SHUT_DOWN.load(Ordering::Relaxed) {
+        console::count_reset_with_label(label);
+    }
+}
+
+/// Start the devtools timer `label` with `console.time`. Its elapsed time is printed by
+/// `console_time_end`, in the format of the browser and outside of the logger: unlike
+/// `time_start` and `log_duration`, it is not styled nor sent to the outputs. Like `count`,
+/// this is not subject to the level and target filters, only to `shutdown`.
+///
+/// ## Examples
+/// ```rust,no_run
+/// wasm_logger::console_time("layout");
+/// // ...
+/// wasm_logger::console_time_end("layout"); // layout: 12.3 ms
+/// ```
+pub fn console_time(label: &str) {
+    if !SHUT_DOWN.load(Ordering::Relaxed) {
+        console::time_with_label(label);
+    }
+}
+
+/// Stop the devtools timer `label` of `console_time` and print its elapsed time with
+/// `console.timeEnd`
+pub fn console_time_end(label: &str) {
+    if !SHUT_DOWN.load(Ordering::Relaxed) {
+        console::time_end_with_label(label);
+    }
+}
+
+/// Set the gauge `name` to `value`, e.g. the frame time or the size of a cache, for the
+/// next `log_gauges`. Setting it again replaces its value.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::set_gauge("fps", 58.0);
+/// wasm_logger::set_gauge("entities", 120.0);
+/// wasm_logger::set_gauge("fps", 60.0);
+/// assert_eq!(
+///     wasm_logger::gauges(),
+///     vec![("entities".to_string(), 120.0), ("fps".to_string(), 60.0)]
+/// );
+///
+/// wasm_logger::remove_gauge("entities");
+/// assert_eq!(wasm_logger::gauges(), vec![("fps".to_string(), 60.0)]);
+///
+/// wasm_logger::clear_gauges();
+/// assert!(wasm_logger::gauges().is_empty());
+/// ```
+pub fn set_gauge(name: &str, value: f64) {
+    GAUGES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(name.to_string(), value);
+}
+
+/// Remove the gauge `name` set by `set_gauge`
+pub fn remove_gauge(name: &str) {
+    GAUGES
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(name);
+}
+
+/// Remove all the gauges set by `set_gauge`
+pub fn clear_gauges() {
+    GAUGES.write().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// The gauges set by `set_gauge` and their current values, sorted by name
+pub fn gauges() -> Vec<(String, f64)> {
+    GAUGES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|(name, value)| (name.clone(), *value))
+        .collect()
+}
+
+/// Log the gauges set by `set_gauge` as a table with `console.table`, e.g. from a
+/// `setInterval` for a performance HUD. Like `count`, this is not subject to the level and
+/// target filters, only to `shutdown`. Nothing is logged if there are no gauges.
+pub fn log_gauges() {
+    if SHUT_DOWN.load(Ordering::Relaxed) {
+        return;
+    }
+    let gauges = GAUGES.read().unwrap_or_else(|e| e.into_inner());
+    if gauges.is_empty() {
+        return;
+    }
+    let table = js_sys::Object::new();
+    for (name, value) in gauges.iter() {
+        let _ = js_sys::Reflect::set(&table, &name.into(), &(*value).into());
+    }
+    console::table_1(&table);
+}
+
+fn log_badge(method: ConsoleMethod, badge: &str, badge_style: &str, message: &str) {
+    if SHUT_DOWN.load(Ordering::Relaxed) {
+        return;
+    }
+    method.call(&js_sys::Array::of3(
+        &JsValue::from_str(&format!("%c{}%c {}", badge, message)),
+        &JsValue::from_str(badge_style),
+        &JsValue::from_str("background: inherit; color: inherit"),
+    ));
+}
+
+/// Log `template` with `objects` as the arguments of its `%o` and `%O` directives, through
+/// the console method of `level`, so that the objects stay inspectable in the devtools.
+/// The other directives of the console, such as `%s` or `%c`, consume `objects` too.
+///
+/// The level filters apply, but not the target rules, because there is no target.
+/// Nothing is rendered around `template`: no level, location or timestamp.
+///
+/// ## Examples
+/// ```rust,no_run
+/// let response = wasm_bindgen::JsValue::from_str("{}");
+/// wasm_logger::logf(log::Level::Debug, "got %o from the server", &[&response]);
+/// ```
+pub fn logf(level: Level, template: &str, objects: &[&JsValue]) {
+    let logger = match active_logger() {
+        Some(logger) => logger,
+        None => return,
+    };
+    if untargeted_enabled(level) {
+        let args: js_sys::Array = std::iter::once(&JsValue::from_str(template))
+            .chain(objects.iter().copied())
+            .collect();
+        logger.console_log(level, &args);
+    }
+}
+
+/// Whether an entry of `level` without target, such as those of `logf`, passes the level
+/// filters
+fn untargeted_enabled(level: Level) -> bool {
+    !SHUT_DOWN.load(Ordering::Relaxed)
+        && ENABLED_LEVELS.load(Ordering::Relaxed) & level_bit(level) != 0
+        && level <= log::STATIC_MAX_LEVEL
+        && level <= log::max_level()
+}
+
+/// Log `label` with `value` as an object, which the devtools can inspect and copy with
+/// "Copy object", and keep the object as the global `__lastLogged`, so that
+/// `copy(__lastLogged)` in the console copies it as JSON, e.g. for a bug report. Like
+/// `logf`, the level filters apply, and nothing is rendered around the label.
+///
+/// Requires the `serde` feature.
+///
+/// ## Examples
+/// ```rust
+/// let settings = serde_json::json!({ "theme": "dark", "volume": 7 });
+/// wasm_logger::log_copyable(log::Level::Debug, "settings", &settings);
+/// ```
+#[cfg(feature = "serde")]
+#[track_caller]
+pub fn log_copyable<T: serde::Serialize>(level: Level, label: &str, value: &T) {
+    if active_logger().is_none() || !untargeted_enabled(level) {
+        return;
+    }
+    let object = match serde_json::to_string(value) {
+        Ok(json) => match js_sys::JSON::parse(&json) {
+            Ok(object) => object,
+            Err(_) => return,
+        },
+        Err(e) => {
+            log_at(
+                level,
+                std::panic::Location::caller(),
+                format_args!("{}: can not be serialized: {}", label, e),
+            );
+            return;
+        }
+    };
+    let _ = js_sys::Reflect::set(&js_sys::global(), &"__lastLogged".into(), &object);
+    logf(
+        level,
+        &format!("{} %o", label.replace('%', "%%")),
+        &[&object],
+    );
+}
+
+/// A `std::fmt::Write` which logs the text written to it, one entry per line, for the
+/// code which writes to a writer rather than calls `log`. A line is logged once its `\n`
+/// is written, so partial writes are buffered. The last line, if not terminated, is logged
+/// by `ConsoleWriter::flush` or when the writer is dropped.
+///
+/// ## Examples
+/// ```rust
+/// use std::fmt::Write;
+///
+/// let mut writer = wasm_logger::ConsoleWriter::new(log::Level::Debug).target("my_app::report");
+/// write!(writer, "{} rows", 3).unwrap();
+/// writeln!(writer, " loaded").unwrap(); // logs `3 rows loaded`
+/// ```
+#[derive(Debug)]
+pub struct ConsoleWriter {
+    level: Level,
+    target: String,
+    line: String,
+    /// Where the writer was created, the location of its entries
+    caller: &'static std::panic::Location<'static>,
+}
+
+impl ConsoleWriter {
+    /// A writer which logs at `level`, with the target `wasm_logger` and the file and line
+    /// of the caller
+    #[track_caller]
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            target: "wasm_logger".to_string(),
+            line: String::new(),
+            caller: std::panic::Location::caller(),
+        }
+    }
+
+    /// Log the entries with `target` instead, so that the target rules apply to them
+    pub fn target(mut self, target: &str) -> Self {
+        self.target = target.to_string();
+        self
+    }
+
+    /// Log the buffered line, if any, even though it is not terminated
+    pub fn flush(&mut self) {
+        if !self.line.is_empty() {
+            let line = std::mem::take(&mut self.line);
+            log_at_target(
+                self.level,
+                &self.target,
+                self.caller,
+                format_args!("{}", line),
+            );
+        }
+    }
+}
+
+impl std::fmt::Write for ConsoleWriter {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.line.push_str(first);
+        }
+        for line in lines {
+            let complete = std::mem::replace(&mut self.line, line.to_string());
+            log_at_target(
+                self.level,
+                &self.target,
+                self.caller,
+                format_args!("{}", complete),
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ConsoleWriter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Log what changed between `before` and `after`, as one `path: old -> new` line per
+/// changed value, e.g. `state.user.name: "a" -> "b"`. Both values are serialized to JSON
+/// and compared deeply, fields which did not change are omitted. A value which only
+/// exists on one side is shown as `(absent)` on the other.
+///
+/// Requires the `serde` feature.
+///
+/// ## Examples
+/// ```rust
+/// let before = serde_json::json!({ "user": { "name": "a", "age": 30 } });
+/// let after = serde_json::json!({ "user": { "name": "b", "age": 30 } });
+/// // Logs `state` and `state.user.name: "a" -> "b"`
+/// wasm_logger::log_diff(log::Level::Debug, "state", &before, &after);
+/// ```
+#[cfg(feature = "serde")]
+#[track_caller]
+pub fn log_diff<T: serde::Serialize>(level: Level, label: &str, before: &T, after: &T) {
+    let values = serde_json::to_value(before)
+        .and_then(|before| serde_json::to_value(after).map(|after| (before, after)));
+    let caller = std::panic::Location::caller();
+    match values {
+        Ok((before, after)) => {
+            let mut changes = Vec::new();
+            json_diff(label, Some(&before), Some(&after), &mut changes);
+            if changes.is_empty() {
+                log_at(level, caller, format_args!("{}: unchanged", label));
+            } else {
+                log_at(
+                    level,
+                    caller,
+                    format_args!("{}\n{}", label, changes.join("\n")),
+                );
+            }
+        }
+        Err(e) => log_at(
+            level,
+            caller,
+            format_args!("{}: can not be serialized: {}", label, e),
+        ),
+    }
+}
+
+/// Push a `path: old -> new` line to `changes` for each value which differs between
+/// `before` and `after`, recursing into objects and arrays
+#[cfg(feature = "serde")]
+fn json_diff(
+    path: &str,
+    before: Option<&serde_json::Value>,
+    after: Option<&serde_json::Value>,
+    changes: &mut Vec<String>,
+) {
+    use serde_json::Value;
+    let show = |value: Option<&Value>| value.map_or("(absent)".to_string(), Value::to_string);
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = format!("{}.{}", path, key);
+                json_diff(&path, before.get(key), after.get(key), changes);
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) => {
+            for i in 0..before.len().max(after.len()) {
+                let path = format!("{}[{}]", path, i);
+                json_diff(&path, before.get(i), after.get(i), changes);
+            }
+        }
+        (before, after) if before != after => {
+            changes.push(format!("{}: {} -> {}", path, show(before), show(after)));
+        }
+        _ => {}
+    }
+}
+
+/// Log the message built by `message` for `target`, only calling `message` if the entry
+/// passes all the filters of the logger, including the target rules which
+/// `log::log_enabled!` does not know about. Use it when building the message is expensive.
+///
+/// ## Examples
+/// ```rust
+/// wasm_logger::init(wasm_logger::Config::new(log::Level::Info).module_prefix("app"));
+///
+/// // Neither closure is called: the first entry is filtered out by its level,
+/// // the second one by its target
+/// wasm_logger::log_lazy(log::Level::Debug, "app", || unreachable!());
+/// wasm_logger::log_lazy(log::Level::Info, "vendor", || unreachable!());
+/// ```
+#[track_caller]
+pub fn log_lazy<F: FnOnce() -> String>(level: Level, target: &str, message: F) {
+    let metadata = Metadata::builder().level(level).target(target).build();
+    if log::logger().enabled(&metadata) {
+        log_at_target(
+            level,
+            target,
+            std::panic::Location::caller(),
+            format_args!("{}", message()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    thread_local! {
+        /// The calls of `WasmLogger::target_enabled` on this thread
+        pub(crate) static TARGET_MATCHES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// Held by the tests which depend on the global state: the max level and the focus
+    static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn enabled_matches_the_target_only_within_the_max_level() {
+        let _state = GLOBAL_STATE.lock().unwrap_or_else(|e| e.into_inner());
+        init(Config::new(Level::Info).module_prefix("app"));
+        let metadata = |level| Metadata::builder().level(level).target("app::net").build();
+        TARGET_MATCHES.with(|matches| matches.set(0));
+        assert!(!log::logger().enabled(&metadata(Level::Debug)));
+        assert_eq!(TARGET_MATCHES.with(Cell::get), 0);
+        assert!(log::logger().enabled(&metadata(Level::Info)));
+        assert_eq!(TARGET_MATCHES.with(Cell::get), 1);
+    }
+
+    #[test]
+    fn module_levels_match_the_longest_prefix() {
+        let rules = [
+            ("", LevelFilter::Warn),
+            ("app", LevelFilter::Info),
+            ("app::", LevelFilter::Debug),
+            ("app::db", LevelFilter::Trace),
+            ("app::db::pool", LevelFilter::Off),
+            ("app::ui::", LevelFilter::Error),
+            ("vendor::", LevelFilter::Error),
+            ("app::db", LevelFilter::Error),
+        ]
+        .map(|(prefix, level)| (prefix.to_string(), level));
+        // The scan of every rule which `ModuleLevels` replaced
+        let linear = |target: &str| {
+            rules
+                .iter()
+                .filter(|(prefix, _)| matches_module_prefix(target, prefix))
+                .max_by_key(|(prefix, _)| prefix.len())
+                .map(|(_, level)| *level)
+        };
+        let levels = ModuleLevels::new(&rules);
+        for target in [
+            "",
+            "app",
+            "application",
+            "app::",
+            "app::db",
+            "app::db2",
+            "app::db::pool",
+            "app::db::pool::conn",
+            "app::dbx::pool",
+            "app::ui",
+            "app::ui::button",
+            "vendor",
+            "vendor::lib",
+            "other::module",
+        ] {
+            // Twice, the second time from the cache
+            assert_eq!(levels.get(target), linear(target), "{target}");
+            assert_eq!(levels.get(target), linear(target), "{target}");
+        }
+    }
+
+    #[test]
+    fn boost_level_keeps_the_module_levels() {
+        let config = Config::new(Level::Info)
+            .parse_directives("info,app::net=trace,hyper=off")
+            .unwrap();
+        let logger = WasmLogger::new(config);
+        let enabled = |level, target| {
+            logger.module_level_enabled(&Metadata::builder().level(level).target(target).build())
+        };
+        // As set by `boost_level(Level::Debug, ..)` over the max level of `init`, which the
+        // module level of `app::net` raised to trace
+        BOOST.with(|boost| boost.set(Some((LevelFilter::Trace, LevelFilter::Debug, 0))));
+        assert!(enabled(Level::Debug, "app"));
+        assert!(!enabled(Level::Trace, "app"));
+        assert!(enabled(Level::Trace, "app::net"));
+        assert!(!enabled(Level::Error, "hyper"));
+        BOOST.with(|boost| boost.set(None));
+        assert!(!enabled(Level::Debug, "app"));
+    }
+
+    #[test]
+    fn empty_targets_pass_no_target_rule() {
+        let _state = GLOBAL_STATE.lock().unwrap_or_else(|e| e.into_inner());
+        log::set_max_level(LevelFilter::Trace);
+        let empty = Metadata::builder().level(Level::Info).target("").build();
+        let enabled = |config| WasmLogger::new(config).enabled(&empty);
+        let config = || Config::new(Level::Info);
+        assert!(enabled(config()));
+        assert!(!enabled(config().module_prefix("app")));
+        assert!(!enabled(config().exact_target("app")));
+        let always_on = config().exact_target("app").always_on_target("app::audit");
+        assert!(!enabled(always_on));
+        focus(&["app"]);
+        let focused = enabled(config());
+        focus_clear();
+        assert!(!focused);
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn repeated_timestamps_are_elided_after_an_emitted_entry() {
+        // Timestamps from a key-value, to render without a browser
+        let config = Config::new(Level::Info)
+            .timestamp_format(TimestampFormat::TimeOnly)
+            .timestamp_from_kv("ts")
+            .elide_repeated_timestamps(true);
+        let kvs = [("ts", 5)];
+        let record = Record::builder()
+            .args(format_args!("burst"))
+            .level(Level::Info)
+            .file(Some("src/main.rs"))
+            .line(Some(3))
+            .key_values(&kvs)
+            .build();
+        let shown = "INFO 00:00:00.005 src/main.rs:3 burst";
+        let elided = "INFO              src/main.rs:3 burst";
+        assert_eq!(render(&config, &record).plain_text(), shown);
+        assert_eq!(render(&config, &record).plain_text(), shown);
+
+        let logger = WasmLogger::new(config);
+        let (rendered, timestamp) = logger.render(&record, None);
+        assert_eq!(rendered.plain_text(), shown);
+        // Not emitted, e.g. dropped by `Config::pre_emit`
+        assert_eq!(logger.render(&record, None).0.plain_text(), shown);
+        logger.emitted(timestamp);
+        assert_eq!(logger.render(&record, None).0.plain_text(), elided);
+    }
 
-    match log::set_boxed_logger(Box::new(wl)) {
-        Ok(_) => log::set_max_level(max_level.to_level_filter()),
-        Err(e) => console::error_1(&JsValue::from(e.to_string())),
+    #[test]
+    fn a_config_registered_again_frees_the_previous_one() {
+        let registered = || {
+            let configs = CONFIGS.read().unwrap_or_else(|e| e.into_inner());
+            let (_, logger) = configs.iter().find(|(name, _)| name == "replaced").unwrap();
+            Arc::downgrade(logger)
+        };
+        register_config("replaced", Config::new(Level::Info));
+        let previous = registered();
+        register_config("replaced", Config::new(Level::Debug));
+        assert!(previous.upgrade().is_none());
+        assert!(registered().upgrade().is_some());
     }
 }
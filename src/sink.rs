@@ -0,0 +1,53 @@
+//! The delivery of the entries: the `Sink` trait of `Config::add_sink`, the hook of
+//! `Config::pre_emit` which decides whether an entry is logged, the `RecordData` of
+//! `Config::on_record`, and the hook of `Config::on_delivery_failure`.
+use crate::RenderedLog;
+use log::Level;
+
+/// Receives the entries of some levels, see `Config::add_sink`
+pub trait Sink: Send + Sync {
+    /// Deliver the entry `rendered`, of `level` and `target`. It is called synchronously
+    /// while the entry is logged, after the console and the built-in outputs, so a slow
+    /// sink should queue the entry rather than block. The entries which it logs are
+    /// [dropped](crate#nested-entries).
+    fn write(&self, level: Level, target: &str, rendered: &RenderedLog);
+}
+
+/// The hook of `Config::pre_emit`
+pub type PreEmitHook = Box<dyn Fn(Level, &RenderedLog) -> EmitDecision + Send + Sync>;
+
+/// What to do with an entry, decided by the hook of `Config::pre_emit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EmitDecision {
+    /// Log the entry as rendered
+    Emit,
+    /// Log this entry instead
+    EmitModified(RenderedLog),
+    /// Do not log the entry
+    Drop,
+}
+
+/// The hook of `Config::on_delivery_failure`, called with the number of lost entries
+pub type DeliveryFailureHook = Box<dyn Fn(usize) + Send + Sync>;
+
+/// The hook of `Config::on_record`
+pub type RecordHook = Box<dyn Fn(RecordData) + Send + Sync>;
+
+/// An owned copy of a logged record, given to the hook of `Config::on_record`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordData {
+    /// The level of the record
+    pub level: Level,
+    /// The target as displayed, see `Config::empty_target_placeholder`
+    pub target: String,
+    /// The source file of the record, if known
+    pub file: Option<String>,
+    /// The line of the record in `file`, if known
+    pub line: Option<u32>,
+    /// Milliseconds since the Unix epoch, from `Config::timestamp_from_kv` if it applies
+    pub timestamp_millis: i64,
+    /// The message of the record, without the layout of the logger
+    pub message: String,
+    /// The key-values of the record, formatted. Always empty without the `kv` feature.
+    pub key_values: Vec<(String, String)>,
+}